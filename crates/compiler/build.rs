@@ -0,0 +1,5 @@
+// Everything the compiled docs depend on besides the inputs, a change invalidates what was
+// generated before
+fn main() {
+    source_hash::emit(&["src", "../docs/src"]);
+}
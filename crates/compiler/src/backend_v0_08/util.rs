@@ -1,9 +1,16 @@
 use super::naga;
+use super::to_wgsl::{build_ty, resolve_name};
 use docs::{
     AddressSpace, Binding, BuiltIn, Expression, Interpolation, Literal, ResourceBinding, Sampling,
 };
+use std::collections::HashMap;
 
-pub fn build_expression(constant_inner: &naga::ConstantInner) -> Expression {
+pub fn build_expression(
+    constant_inner: &naga::ConstantInner,
+    types: &naga::UniqueArena<naga::Type>,
+    constants: &naga::Arena<naga::Constant>,
+    def_paths: &HashMap<String, Vec<String>>,
+) -> Expression {
     match constant_inner {
         naga::ConstantInner::Scalar { value, .. } => Expression::Literal(match *value {
             naga::ScalarValue::Sint(v) => Literal::I64(v),
@@ -11,7 +18,22 @@ pub fn build_expression(constant_inner: &naga::ConstantInner) -> Expression {
             naga::ScalarValue::Float(v) => Literal::F64(v),
             naga::ScalarValue::Bool(v) => Literal::Bool(v),
         }),
-        _ => Expression::Unknown,
+        naga::ConstantInner::Composite { ty, components } => Expression::Compose {
+            ty: build_ty(&types[*ty], types, constants, def_paths),
+            components: components
+                .iter()
+                .map(|&handle| {
+                    let constant = &constants[handle];
+                    match &constant.name {
+                        Some(name) => {
+                            let (name, def_path) = resolve_name(name, def_paths);
+                            Expression::Constant { name, def_path }
+                        }
+                        None => build_expression(&constant.inner, types, constants, def_paths),
+                    }
+                })
+                .collect(),
+        },
     }
 }
 
@@ -4,7 +4,11 @@ mod util;
 use naga_oil_v0_11 as naga_oil;
 use naga_v0_14 as naga;
 
-use crate::{common, download::ShaderSource};
+use crate::{
+    common,
+    download::ShaderSource,
+    extract::{DefPaths, Import, NagaAdapter, Shader},
+};
 use docs::*;
 use naga::TypeInner;
 use naga_oil::compose::{
@@ -18,28 +22,102 @@ use util::{build_address_space, build_binding, build_expression, build_resource_
 const NAGA_OIL_DECORATION_PRE: &str = "X_naga_oil_mod_X";
 const NAGA_OIL_DECORATION_POST: &str = "X";
 
-pub fn compile(
-    root_crate_name: &str,
-    root_crate_version: Version,
-    shader_def_values: IndexMap<String, ShaderDefValue>,
-    shader_sources: Vec<ShaderSource>,
-) -> Result<Doc, Box<dyn std::error::Error>> {
-    let (shaders, mut composer) = compile_shaders(shader_sources)?;
+struct Preprocessed {
+    source: ShaderSource,
+    imports: Vec<ImportDefinition>,
+    defines: HashMap<String, compose::ShaderDefValue>,
+}
+
+pub struct Adapter {
+    composer: Composer,
+}
+
+pub struct ComposedModule {
+    naga: naga::Module,
+    layouter: naga::proc::Layouter,
+}
+
+impl NagaAdapter for Adapter {
+    type Module = ComposedModule;
+
+    const DECORATION: &'static str = NAGA_OIL_DECORATION_PRE;
+
+    fn compose(
+        shader_sources: Vec<ShaderSource>,
+    ) -> Result<(Self, Vec<Shader>), Box<dyn std::error::Error>> {
+        let mut composer = Composer::default();
+        let mut shaders = HashMap::new();
+
+        for shader_source in shader_sources {
+            let (import_path, imports, defines) =
+                compose::get_preprocessor_data(&shader_source.source);
+            if let Some(import_path) = import_path {
+                shaders.insert(
+                    import_path,
+                    Preprocessed {
+                        source: shader_source,
+                        imports,
+                        defines,
+                    },
+                );
+            }
+        }
+
+        fn add_to_composer(
+            composer: &mut Composer,
+            name: &str,
+            shaders: &HashMap<String, Preprocessed>,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            if !composer.contains_module(name) {
+                let this = match shaders.get(name) {
+                    Some(this) => this,
+                    None => return Err(format!("shader not found: {}", name).into()),
+                };
+
+                for import in &this.imports {
+                    add_to_composer(composer, &import.import, shaders)?;
+                }
 
-    let mut doc = common::doc_new(
-        root_crate_name.to_string(),
-        root_crate_version,
-        shader_def_values.clone(),
-    );
+                composer.add_composable_module(ComposableModuleDescriptor {
+                    source: &this.source.source,
+                    file_path: &this.source.path.to_string_lossy(),
+                    language: ShaderLanguage::Wgsl,
+                    additional_imports: Default::default(),
+                    shader_defs: this.defines.clone(),
+                    as_name: None,
+                })?;
+            }
 
-    for (import_path, shader) in &shaders {
-        let (module_path, module) = common::find_or_create_module(&mut doc, import_path);
+            Ok(())
+        }
+        for name in shaders.keys() {
+            add_to_composer(&mut composer, name, &shaders)?;
+        }
 
-        module
-            .shader_defs
-            .extend(shader.source.shader_defs.iter().cloned());
-        module.source_url = Some(shader.source.docsrs_url.clone());
+        let shaders = shaders
+            .into_iter()
+            .map(|(import_path, shader)| Shader {
+                import_path,
+                source: shader.source,
+                imports: shader
+                    .imports
+                    .into_iter()
+                    .map(|import| Import {
+                        path: import.import.split("::").map(str::to_owned).collect(),
+                        items: import.items,
+                    })
+                    .collect(),
+            })
+            .collect();
 
+        Ok((Self { composer }, shaders))
+    }
+
+    fn make_naga_module(
+        &mut self,
+        shader: &Shader,
+        shader_def_values: &IndexMap<String, ShaderDefValue>,
+    ) -> Result<ComposedModule, Box<dyn std::error::Error>> {
         let desc = NagaModuleDescriptor {
             source: &shader.source.source,
             shader_defs: shader_def_values
@@ -57,158 +135,126 @@ pub fn compile(
                 .collect(),
             ..Default::default()
         };
-        let naga_module = composer.make_naga_module(desc)?;
-        let gctx = naga_module.to_ctx();
+        let naga = self.composer.make_naga_module(desc)?;
 
-        let mut def_paths = HashMap::new();
-        for import in &shader.imports {
-            let module_path = import
-                .import
-                .split("::")
-                .map(str::to_owned)
-                .collect::<Vec<_>>();
-            for item in &import.items {
-                def_paths.insert(item.clone(), module_path.clone());
-            }
-        }
-        for (_handle, ty) in naga_module.types.iter() {
-            if !contains_pre(ty.name.as_deref()) {
-                if let TypeInner::Struct { .. } = &ty.inner {
-                    if let Some(name) = &ty.name {
-                        def_paths.insert(name.clone(), module_path.clone());
-                    }
-                }
-            }
-        }
+        let mut layouter = naga::proc::Layouter::default();
+        layouter.update(naga.to_ctx())?;
 
-        for (_handle, constant) in naga_module.constants.iter() {
-            if !contains_pre(constant.name.as_deref()) {
-                module.constants.push(Constant {
-                    name: Ident::from(constant.name.clone()),
-                    ty: build_ty(&naga_module.types[constant.ty], gctx, &def_paths),
-                    init: build_expression(&naga_module.const_expressions[constant.init]),
-                });
-            }
-        }
-
-        for (_handle, var) in naga_module.global_variables.iter() {
-            if !contains_pre(var.name.as_deref()) {
-                module.global_variables.push(GlobalVariable {
-                    name: Ident::from(var.name.clone()),
-                    space: build_address_space(&var.space),
-                    binding: var.binding.as_ref().map(build_resource_binding),
-                    ty: build_ty(&naga_module.types[var.ty], gctx, &def_paths),
-                    init: var
-                        .init
-                        .map(|init| build_expression(&naga_module.const_expressions[init])),
-                });
-            }
-        }
+        Ok(ComposedModule { naga, layouter })
+    }
 
-        for (_handle, ty) in naga_module.types.iter() {
-            if !contains_pre(ty.name.as_deref()) {
-                if let TypeInner::Struct { members, .. } = &ty.inner {
-                    module.structs.push(Struct {
-                        name: Ident::from(ty.name.clone()),
-                        members: members
-                            .iter()
-                            .map(|member| StructMember {
-                                name: Ident::from(member.name.clone()),
-                                ty: build_ty(&naga_module.types[member.ty], gctx, &def_paths),
-                                binding: member.binding.as_ref().map(build_binding),
-                            })
-                            .collect(),
-                    });
-                }
-            }
-        }
+    fn item_names(module: &ComposedModule) -> Vec<String> {
+        let naga_module = &module.naga;
+        let structs = naga_module
+            .types
+            .iter()
+            .filter_map(|(_, ty)| match ty.inner {
+                TypeInner::Struct { .. } => ty.name.clone(),
+                _ => None,
+            });
+        let constants = naga_module
+            .constants
+            .iter()
+            .filter_map(|(_, constant)| constant.name.clone());
 
-        for (_handle, function) in naga_module.functions.iter() {
-            if !contains_pre(function.name.as_deref()) {
-                module.functions.push(Function {
-                    name: Ident::from(function.name.clone()),
-                    arguments: function
-                        .arguments
-                        .iter()
-                        .map(|arg| FunctionArgument {
-                            name: Ident::from(arg.name.clone()),
-                            ty: build_ty(&naga_module.types[arg.ty], gctx, &def_paths),
-                            binding: arg.binding.as_ref().map(build_binding),
-                        })
-                        .collect(),
-                    ret: function
-                        .result
-                        .as_ref()
-                        .map(|res| build_ty(&naga_module.types[res.ty], gctx, &def_paths)),
-                });
-            }
-        }
+        structs.chain(constants).collect()
     }
 
-    Ok(doc)
-}
-
-fn contains_pre(name: Option<&str>) -> bool {
-    name.map(|name| name.contains(NAGA_OIL_DECORATION_PRE))
-        .unwrap_or(false)
-}
+    fn constants(module: &ComposedModule, def_paths: &DefPaths) -> Vec<Constant> {
+        let naga_module = &module.naga;
+        let gctx = naga_module.to_ctx();
 
-struct Shader {
-    source: ShaderSource,
-    imports: Vec<ImportDefinition>,
-    defines: HashMap<String, compose::ShaderDefValue>,
-}
+        naga_module
+            .constants
+            .iter()
+            .map(|(_handle, constant)| Constant {
+                name: Ident::from(constant.name.clone()),
+                ty: build_ty(&naga_module.types[constant.ty], gctx, def_paths),
+                init: build_expression(
+                    &naga_module.const_expressions[constant.init],
+                    gctx,
+                    def_paths,
+                ),
+            })
+            .collect()
+    }
 
-fn compile_shaders(
-    shader_sources: Vec<ShaderSource>,
-) -> Result<(HashMap<String, Shader>, Composer), Box<dyn std::error::Error>> {
-    let mut composer = Composer::default();
-    let mut shaders = HashMap::new();
+    fn global_variables(module: &ComposedModule, def_paths: &DefPaths) -> Vec<GlobalVariable> {
+        let naga_module = &module.naga;
+        let gctx = naga_module.to_ctx();
 
-    for shader_source in shader_sources {
-        let (import_path, imports, defines) = compose::get_preprocessor_data(&shader_source.source);
-        if let Some(import_path) = import_path {
-            shaders.insert(
-                import_path,
-                Shader {
-                    source: shader_source,
-                    imports,
-                    defines,
-                },
-            );
-        }
+        naga_module
+            .global_variables
+            .iter()
+            .map(|(_handle, var)| GlobalVariable {
+                name: Ident::from(var.name.clone()),
+                space: build_address_space(&var.space),
+                binding: var.binding.as_ref().map(build_resource_binding),
+                ty: build_ty(&naga_module.types[var.ty], gctx, def_paths),
+                init: var.init.map(|init| {
+                    build_expression(&naga_module.const_expressions[init], gctx, def_paths)
+                }),
+            })
+            .collect()
     }
 
-    fn add_to_composer(
-        composer: &mut Composer,
-        name: &str,
-        shaders: &HashMap<String, Shader>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if !composer.contains_module(name) {
-            let this = match shaders.get(name) {
-                Some(this) => this,
-                None => return Err(format!("shader not found: {}", name).into()),
-            };
-
-            for import in &this.imports {
-                add_to_composer(composer, &import.import, shaders)?;
-            }
-
-            composer.add_composable_module(ComposableModuleDescriptor {
-                source: &this.source.source,
-                file_path: &this.source.path.to_string_lossy(),
-                language: ShaderLanguage::Wgsl,
-                additional_imports: Default::default(),
-                shader_defs: this.defines.clone(),
-                as_name: None,
-            })?;
-        }
+    fn structs(module: &ComposedModule, def_paths: &DefPaths) -> Vec<Struct> {
+        let naga_module = &module.naga;
+        let layouter = &module.layouter;
+        let gctx = naga_module.to_ctx();
 
-        Ok(())
-    }
-    for name in shaders.keys() {
-        add_to_composer(&mut composer, name, &shaders)?;
+        naga_module
+            .types
+            .iter()
+            .filter_map(|(handle, ty)| match &ty.inner {
+                TypeInner::Struct { members, span } => Some(Struct {
+                    name: Ident::from(ty.name.clone()),
+                    members: members
+                        .iter()
+                        .map(|member| StructMember {
+                            name: Ident::from(member.name.clone()),
+                            ty: build_ty(&naga_module.types[member.ty], gctx, def_paths),
+                            binding: member.binding.as_ref().map(build_binding),
+                            offset: member.offset,
+                            size: layouter[member.ty].size,
+                            align: common::alignment_bytes(layouter[member.ty].alignment),
+                            stride: match naga_module.types[member.ty].inner {
+                                TypeInner::Array { stride, .. } => Some(stride),
+                                _ => None,
+                            },
+                        })
+                        .collect(),
+                    size: *span,
+                    align: common::alignment_bytes(layouter[handle].alignment),
+                }),
+                _ => None,
+            })
+            .collect()
     }
 
-    Ok((shaders, composer))
+    fn functions(module: &ComposedModule, def_paths: &DefPaths) -> Vec<Function> {
+        let naga_module = &module.naga;
+        let gctx = naga_module.to_ctx();
+
+        naga_module
+            .functions
+            .iter()
+            .map(|(_handle, function)| Function {
+                name: Ident::from(function.name.clone()),
+                arguments: function
+                    .arguments
+                    .iter()
+                    .map(|arg| FunctionArgument {
+                        name: Ident::from(arg.name.clone()),
+                        ty: build_ty(&naga_module.types[arg.ty], gctx, def_paths),
+                        binding: arg.binding.as_ref().map(build_binding),
+                    })
+                    .collect(),
+                ret: function
+                    .result
+                    .as_ref()
+                    .map(|res| build_ty(&naga_module.types[res.ty], gctx, def_paths)),
+            })
+            .collect()
+    }
 }
@@ -1,5 +1,6 @@
 use super::naga::{self, proc::GlobalCtx, Scalar, TypeInner};
 use super::{NAGA_OIL_DECORATION_POST, NAGA_OIL_DECORATION_PRE};
+use crate::common;
 use std::collections::HashMap;
 
 pub fn build_ty(
@@ -9,20 +10,33 @@ pub fn build_ty(
 ) -> docs::Type {
     match &ty.name {
         Some(name) => {
-            let pre_pos = name.find(NAGA_OIL_DECORATION_PRE);
-            let ends_with_post = name.ends_with(NAGA_OIL_DECORATION_POST);
-
-            let name = match (pre_pos, ends_with_post) {
-                (Some(pre_pos), true) => name[..pre_pos].to_string(),
-                _ => name.clone(),
-            };
-            let def_path = def_paths.get(&name).cloned();
+            let (name, def_path) = resolve_name(name, def_paths);
             docs::Type::Named { name, def_path }
         }
         None => build_ty_inner(&ty.inner, gctx, def_paths),
     }
 }
 
+// Decorated names carry the module they were defined in, everything else is looked up in
+// `def_paths`.
+pub fn resolve_name(
+    name: &str,
+    def_paths: &HashMap<String, Vec<String>>,
+) -> (String, Option<Vec<String>>) {
+    if let Some(pre_pos) = name.find(NAGA_OIL_DECORATION_PRE) {
+        if let Some(encoded) =
+            name[pre_pos + NAGA_OIL_DECORATION_PRE.len()..].strip_suffix(NAGA_OIL_DECORATION_POST)
+        {
+            let name = name[..pre_pos].to_string();
+            let def_path =
+                common::decode_module_path(encoded).or_else(|| def_paths.get(&name).cloned());
+            return (name, def_path);
+        }
+    }
+
+    (name.to_string(), def_paths.get(name).cloned())
+}
+
 // Copy-pasted and adapted from: naga-0.19.2
 
 pub fn build_ty_inner(
@@ -169,7 +183,7 @@ pub fn build_ty_inner(
             // TODO: Actually output the struct?
             "struct".to_string()
         }
-        TypeInner::RayQuery { .. } => {
+        TypeInner::RayQuery => {
             // TODO: ???
             "ray_query".to_string()
         }
@@ -1,26 +1,129 @@
-use super::naga;
+use super::naga::{self, proc::GlobalCtx};
+use super::to_wgsl::{build_ty, resolve_name};
 use docs::{
-    AddressSpace, Binding, BuiltIn, Expression, Interpolation, Literal, ResourceBinding, Sampling,
+    AddressSpace, BinaryOperator, Binding, BuiltIn, Expression, Interpolation, Literal,
+    ResourceBinding, Sampling, UnaryOperator,
 };
+use std::collections::HashMap;
 
-pub fn build_expression(expression: &naga::Expression) -> Expression {
-    match expression {
-        naga::Expression::Literal(lit) => Expression::Literal(match *lit {
-            naga::Literal::F64(v) => Literal::F64(v),
-            naga::Literal::F32(v) => Literal::F32(v),
-            naga::Literal::F16(v) => Literal::F16(v.into()),
-            naga::Literal::U32(v) => Literal::U32(v),
-            naga::Literal::I32(v) => Literal::I32(v),
-            naga::Literal::Bool(v) => Literal::Bool(v),
-            naga::Literal::I64(v) => Literal::I64(v),
-            naga::Literal::U64(v) => Literal::U64(v),
-            naga::Literal::AbstractInt(v) => Literal::AbstractInt(v),
-            naga::Literal::AbstractFloat(v) => Literal::AbstractFloat(v),
-        }),
+pub fn build_expression(
+    expression: &naga::Expression,
+    gctx: GlobalCtx,
+    def_paths: &HashMap<String, Vec<String>>,
+) -> Expression {
+    let build = |handle: naga::Handle<naga::Expression>| {
+        build_expression(&gctx.global_expressions[handle], gctx, def_paths)
+    };
+
+    match *expression {
+        naga::Expression::Literal(lit) => Expression::Literal(build_literal(lit)),
+        naga::Expression::Constant(handle) => {
+            let constant = &gctx.constants[handle];
+            match &constant.name {
+                Some(name) => {
+                    let (name, def_path) = resolve_name(name, def_paths);
+                    Expression::Constant { name, def_path }
+                }
+                None => build(constant.init),
+            }
+        }
+        naga::Expression::Override(handle) => match &gctx.overrides[handle].name {
+            Some(name) => {
+                let (name, def_path) = resolve_name(name, def_paths);
+                Expression::Override { name, def_path }
+            }
+            None => Expression::Unknown,
+        },
+        naga::Expression::ZeroValue(ty) => {
+            Expression::ZeroValue(build_ty(&gctx.types[ty], gctx, def_paths))
+        }
+        naga::Expression::Compose { ty, ref components } => Expression::Compose {
+            ty: build_ty(&gctx.types[ty], gctx, def_paths),
+            components: components.iter().map(|&handle| build(handle)).collect(),
+        },
+        naga::Expression::Splat { size, value } => Expression::Splat {
+            size: size as u32,
+            value: Box::new(build(value)),
+        },
+        naga::Expression::Unary { op, expr } => Expression::Unary {
+            op: build_unary_operator(op),
+            expr: Box::new(build(expr)),
+        },
+        naga::Expression::Binary { op, left, right } => Expression::Binary {
+            op: build_binary_operator(op),
+            left: Box::new(build(left)),
+            right: Box::new(build(right)),
+        },
+        naga::Expression::As {
+            expr,
+            kind,
+            convert,
+        } => Expression::As {
+            expr: Box::new(build(expr)),
+            scalar: build_scalar(kind, convert.unwrap_or(4)),
+            convert: convert.is_some(),
+        },
         _ => Expression::Unknown,
     }
 }
 
+pub fn build_literal(literal: naga::Literal) -> Literal {
+    match literal {
+        naga::Literal::F64(v) => Literal::F64(v),
+        naga::Literal::F32(v) => Literal::F32(v),
+        naga::Literal::F16(v) => Literal::F16(v.into()),
+        naga::Literal::U32(v) => Literal::U32(v),
+        naga::Literal::I32(v) => Literal::I32(v),
+        naga::Literal::Bool(v) => Literal::Bool(v),
+        naga::Literal::I64(v) => Literal::I64(v),
+        naga::Literal::U64(v) => Literal::U64(v),
+        naga::Literal::AbstractInt(v) => Literal::AbstractInt(v),
+        naga::Literal::AbstractFloat(v) => Literal::AbstractFloat(v),
+    }
+}
+
+pub fn build_unary_operator(op: naga::UnaryOperator) -> UnaryOperator {
+    match op {
+        naga::UnaryOperator::Negate => UnaryOperator::Negate,
+        naga::UnaryOperator::LogicalNot => UnaryOperator::LogicalNot,
+        naga::UnaryOperator::BitwiseNot => UnaryOperator::BitwiseNot,
+    }
+}
+
+pub fn build_binary_operator(op: naga::BinaryOperator) -> BinaryOperator {
+    match op {
+        naga::BinaryOperator::Add => BinaryOperator::Add,
+        naga::BinaryOperator::Subtract => BinaryOperator::Subtract,
+        naga::BinaryOperator::Multiply => BinaryOperator::Multiply,
+        naga::BinaryOperator::Divide => BinaryOperator::Divide,
+        naga::BinaryOperator::Modulo => BinaryOperator::Modulo,
+        naga::BinaryOperator::Equal => BinaryOperator::Equal,
+        naga::BinaryOperator::NotEqual => BinaryOperator::NotEqual,
+        naga::BinaryOperator::Less => BinaryOperator::Less,
+        naga::BinaryOperator::LessEqual => BinaryOperator::LessEqual,
+        naga::BinaryOperator::Greater => BinaryOperator::Greater,
+        naga::BinaryOperator::GreaterEqual => BinaryOperator::GreaterEqual,
+        naga::BinaryOperator::And => BinaryOperator::And,
+        naga::BinaryOperator::ExclusiveOr => BinaryOperator::ExclusiveOr,
+        naga::BinaryOperator::InclusiveOr => BinaryOperator::InclusiveOr,
+        naga::BinaryOperator::LogicalAnd => BinaryOperator::LogicalAnd,
+        naga::BinaryOperator::LogicalOr => BinaryOperator::LogicalOr,
+        naga::BinaryOperator::ShiftLeft => BinaryOperator::ShiftLeft,
+        naga::BinaryOperator::ShiftRight => BinaryOperator::ShiftRight,
+    }
+}
+
+pub fn build_scalar(kind: naga::ScalarKind, width: u8) -> String {
+    match kind {
+        naga::ScalarKind::Sint => format!("i{}", width * 8),
+        naga::ScalarKind::Uint => format!("u{}", width * 8),
+        naga::ScalarKind::Float => format!("f{}", width * 8),
+        naga::ScalarKind::Bool => "bool".to_string(),
+        naga::ScalarKind::AbstractInt => "{AbstractInt}".to_string(),
+        naga::ScalarKind::AbstractFloat => "{AbstractFloat}".to_string(),
+    }
+}
+
 pub fn build_resource_binding(binding: &naga::ResourceBinding) -> ResourceBinding {
     ResourceBinding {
         group: binding.group,
@@ -5,6 +5,7 @@ use super::naga::{
     Scalar, TypeInner,
 };
 use super::{NAGA_OIL_DECORATION_POST, NAGA_OIL_DECORATION_PRE};
+use crate::common;
 use std::collections::HashMap;
 
 pub fn build_ty(
@@ -14,20 +15,47 @@ pub fn build_ty(
 ) -> docs::Type {
     match &ty.name {
         Some(name) => {
-            let pre_pos = name.find(NAGA_OIL_DECORATION_PRE);
-            let ends_with_post = name.ends_with(NAGA_OIL_DECORATION_POST);
-
-            let name = match (pre_pos, ends_with_post) {
-                (Some(pre_pos), true) => name[..pre_pos].to_string(),
-                _ => name.clone(),
-            };
-            let def_path = def_paths.get(&name).cloned();
+            let (name, def_path) = resolve_name(name, def_paths);
             docs::Type::Named { name, def_path }
         }
         None => build_ty_inner(&ty.inner, gctx, def_paths),
     }
 }
 
+fn build_override_name(
+    handle: naga::Handle<naga::Override>,
+    gctx: GlobalCtx,
+    def_paths: &HashMap<String, Vec<String>>,
+) -> (String, Option<Vec<String>>) {
+    match &gctx.overrides[handle].name {
+        Some(name) => {
+            let (name, def_path) = resolve_name(name, def_paths);
+            (name, def_path)
+        }
+        None => ("?".to_string(), None),
+    }
+}
+
+// Decorated names carry the module they were defined in, everything else is looked up in
+// `def_paths`.
+pub fn resolve_name(
+    name: &str,
+    def_paths: &HashMap<String, Vec<String>>,
+) -> (String, Option<Vec<String>>) {
+    if let Some(pre_pos) = name.find(NAGA_OIL_DECORATION_PRE) {
+        if let Some(encoded) =
+            name[pre_pos + NAGA_OIL_DECORATION_PRE.len()..].strip_suffix(NAGA_OIL_DECORATION_POST)
+        {
+            let name = name[..pre_pos].to_string();
+            let def_path =
+                common::decode_module_path(encoded).or_else(|| def_paths.get(&name).cloned());
+            return (name, def_path);
+        }
+    }
+
+    (name.to_string(), def_paths.get(name).cloned())
+}
+
 // Copy-pasted and adapted from: naga-0.19.2
 
 pub fn build_ty_inner(
@@ -102,9 +130,13 @@ pub fn build_ty_inner(
                 naga::ArraySize::Dynamic => {
                     docs::Type::ArrayDynamic(Box::new(build_ty(member_type, gctx, def_paths)))
                 }
-                naga::ArraySize::Pending(_handle) => {
-                    // TODO: should be ArrayPending...
-                    docs::Type::ArrayDynamic(Box::new(build_ty(member_type, gctx, def_paths)))
+                naga::ArraySize::Pending(handle) => {
+                    let (size, def_path) = build_override_name(handle, gctx, def_paths);
+                    docs::Type::ArrayPending {
+                        base: Box::new(build_ty(member_type, gctx, def_paths)),
+                        size,
+                        def_path,
+                    }
                 }
             };
         }
@@ -121,13 +153,13 @@ pub fn build_ty_inner(
                     gctx,
                     def_paths,
                 ))),
-                naga::ArraySize::Pending(_handle) => {
-                    // TODO: should be ArrayPending...
-                    docs::Type::BindingArrayDynamic(Box::new(build_ty(
-                        member_type,
-                        gctx,
-                        def_paths,
-                    )))
+                naga::ArraySize::Pending(handle) => {
+                    let (size, def_path) = build_override_name(handle, gctx, def_paths);
+                    docs::Type::BindingArrayPending {
+                        base: Box::new(build_ty(member_type, gctx, def_paths)),
+                        size,
+                        def_path,
+                    }
                 }
             };
         }
@@ -1,4 +1,9 @@
 use docs::{Doc, IndexMap, IndexSet, Module, ShaderDefValue, Version};
+use std::{any::Any, ops::Mul, thread};
+
+// Upper bound of the workers extracting one doc. `make` compiles several docs at once, so more
+// stop paying off.
+const MAX_WORKERS: usize = 4;
 
 pub fn doc_new(
     root_crate_name: String,
@@ -10,14 +15,106 @@ pub fn doc_new(
         root: Module {
             name: root_crate_name,
             source_url: None,
+            item_source_urls: IndexMap::new(),
             modules: Vec::new(),
             constants: Vec::new(),
+            overrides: Vec::new(),
             global_variables: Vec::new(),
             structs: Vec::new(),
             functions: Vec::new(),
             shader_defs: IndexSet::new(),
+            imports: Vec::new(),
         },
         compiled_with: shader_def_values,
+        extern_crates: IndexMap::new(),
+        input_hash: None,
+        sources: IndexMap::new(),
+    }
+}
+
+// Runs `f` once per worker thread on a partial doc, the partial docs are merged in worker order.
+// Workers receive their index and the number of workers to pick their share of the modules.
+pub fn compile_parallel(
+    doc: &mut Doc,
+    f: impl Fn(&mut Doc, usize, usize) -> Result<(), Box<dyn std::error::Error>> + Sync,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let workers = thread::available_parallelism()
+        .map(|workers| workers.get())
+        .unwrap_or(1)
+        .min(MAX_WORKERS);
+
+    let partials = thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|worker| {
+                let f = &f;
+                let mut partial = doc_new(
+                    doc.root.name.clone(),
+                    doc.version.clone(),
+                    doc.compiled_with.clone(),
+                );
+                scope.spawn(move || {
+                    f(&mut partial, worker, workers)
+                        .map(|()| partial)
+                        .map_err(|err| err.to_string())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .map_err(panic_message)
+                    .and_then(|result| result)
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    for partial in partials {
+        merge_module(&mut doc.root, partial.root);
+    }
+
+    Ok(())
+}
+
+// Alignment in bytes. naga's `Alignment` converts to nothing, every version only implements
+// `Alignment * u32`, so it's scaled by one.
+pub fn alignment_bytes(alignment: impl Mul<u32, Output = u32>) -> u32 {
+    alignment * 1
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause");
+    format!("a compile worker panicked: {}", message)
+}
+
+fn merge_module(into: &mut Module, from: Module) {
+    if into.source_url.is_none() {
+        into.source_url = from.source_url;
+    }
+    into.item_source_urls.extend(from.item_source_urls);
+    into.constants.extend(from.constants);
+    into.overrides.extend(from.overrides);
+    into.global_variables.extend(from.global_variables);
+    into.structs.extend(from.structs);
+    into.functions.extend(from.functions);
+    into.shader_defs.extend(from.shader_defs);
+    into.imports.extend(from.imports);
+
+    for inner in from.modules {
+        match into
+            .modules
+            .iter_mut()
+            .find(|module| module.name == inner.name)
+        {
+            Some(module) => merge_module(module, inner),
+            None => into.modules.push(inner),
+        }
     }
 }
 
@@ -41,12 +138,15 @@ pub fn find_or_create_module<'a>(
                 module.modules.push(Module {
                     name: segment.to_string(),
                     source_url: None,
+                    item_source_urls: IndexMap::new(),
                     modules: Vec::new(),
                     constants: Vec::new(),
+                    overrides: Vec::new(),
                     global_variables: Vec::new(),
                     structs: Vec::new(),
                     functions: Vec::new(),
                     shader_defs: IndexSet::new(),
+                    imports: Vec::new(),
                 });
                 module.modules.last_mut().unwrap()
             }
@@ -55,3 +155,12 @@ pub fn find_or_create_module<'a>(
 
     (path, module)
 }
+
+// naga_oil decorates imported items with the base32 encoded path of their module
+pub fn decode_module_path(encoded: &str) -> Option<Vec<String>> {
+    let decoded = data_encoding::BASE32_NOPAD
+        .decode(encoded.as_bytes())
+        .ok()?;
+    let module = String::from_utf8(decoded).ok()?;
+    Some(module.split("::").map(str::to_owned).collect())
+}
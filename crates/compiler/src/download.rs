@@ -1,10 +1,14 @@
-use crate::CompilerBackend;
+use crate::{
+    git::Snapshot,
+    source_link::{find_declarations, SourceCrate, SourceLinkProvider},
+    CompilerBackend,
+};
 use cargo_metadata::{MetadataCommand, Package};
 use docs::Version;
 use regex::Regex;
 use reqwest::blocking::Client;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::{self, OpenOptions},
     io::Write,
     path::{Path, PathBuf},
@@ -12,11 +16,16 @@ use std::{
 };
 use tar::Archive;
 
+#[derive(Clone)]
 pub struct ShaderSource {
     pub path: PathBuf,
     pub source: String,
     pub shader_defs: HashSet<String>,
-    pub docsrs_url: String,
+    pub source_url: String,
+    // Links to the lines of the top level declarations by name
+    pub item_source_urls: HashMap<String, String>,
+    // Path and unpatched source of the page generated for local source links
+    pub local_page: Option<(String, String)>,
 }
 
 pub fn download_shaders(
@@ -25,6 +34,7 @@ pub fn download_shaders(
     package_filter: impl Fn(&str) -> bool,
     cache_path: &Path,
     backend: CompilerBackend,
+    source_links: &SourceLinkProvider,
 ) -> Result<Vec<ShaderSource>, Box<dyn std::error::Error>> {
     let manifest_path = download_crate(cache_path, root_crate_name, root_crate_version)?;
     let metadata = MetadataCommand::new().manifest_path(manifest_path).exec()?;
@@ -33,7 +43,7 @@ pub fn download_shaders(
 
     for package in &metadata.packages {
         if package.name == "naga_oil" && package.version.minor != backend.naga_oil_minor() {
-            println!(
+            eprintln!(
                 "Warning: naga_oil version mismatch: compiling with {}, found {}",
                 backend.naga_oil_minor(),
                 package.version.minor
@@ -41,50 +51,139 @@ pub fn download_shaders(
         }
 
         if package_filter(&package.name) {
-            let crate_path = package
-                .manifest_path
-                .parent()
-                .unwrap()
-                .to_path_buf()
-                .into_std_path_buf();
-
-            let mut dirs = vec![crate_path.clone()];
-            while let Some(parent) = dirs.pop() {
-                for entry in fs::read_dir(parent)? {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if path.is_dir() {
-                        dirs.push(path);
-                    } else if path.is_file() && path.extension() == Some("wgsl".as_ref()) {
-                        let source = fs::read_to_string(&path)?;
-                        let shader_defs = find_defs(&source);
-
-                        let docsrs_url = {
-                            let mut url = format!(
-                                "https://docs.rs/crate/{}/{}/source",
-                                package.name, package.version
-                            );
-
-                            let local = path.strip_prefix(&crate_path)?;
-                            for segment in local.components() {
-                                url.push('/');
-                                url.push_str(&segment.as_os_str().to_string_lossy());
-                            }
-
-                            url
-                        };
-
-                        let source = fix_bevy_14139(source, package);
-                        let source = fix_bevy_24714(source, package);
-
-                        shaders.push(ShaderSource {
-                            path,
-                            source,
-                            shader_defs,
-                            docsrs_url,
-                        });
-                    }
+            let crate_path = package.manifest_path.parent().unwrap().as_std_path();
+
+            let source_crate = SourceCrate {
+                name: package.name.clone(),
+                version: package.version.to_string(),
+                repository: package.repository.clone(),
+                path_in_repository: SourceCrate::path_in_vcs(crate_path),
+                commit: None,
+            };
+
+            shaders.extend(read_shaders(
+                crate_path,
+                &source_crate,
+                source_links,
+                |source| fix_bevy_24714(fix_bevy_14139(source, package), package),
+            )?);
+        }
+    }
+
+    Ok(shaders)
+}
+
+// Reads the shaders of a crate that is already on disk, used for the offline test fixtures
+pub fn local_shaders(
+    crate_path: &Path,
+    name: &str,
+    version: &Version,
+    source_links: &SourceLinkProvider,
+) -> Result<Vec<ShaderSource>, Box<dyn std::error::Error>> {
+    let source_crate = SourceCrate {
+        name: name.to_string(),
+        version: version.to_string(),
+        repository: None,
+        path_in_repository: PathBuf::new(),
+        commit: None,
+    };
+    read_shaders(crate_path, &source_crate, source_links, |source| source)
+}
+
+// Reads the shaders of the workspace members of a git snapshot. Returns them with the version of
+// the root crate as a prerelease of the commit, e.g. `0.20.0-dev+20261018093000.abc1234`.
+pub fn snapshot_shaders(
+    root_crate_name: &str,
+    snapshot: &Snapshot,
+    package_filter: impl Fn(&str) -> bool,
+    source_links: &SourceLinkProvider,
+) -> Result<(Version, Vec<ShaderSource>), Box<dyn std::error::Error>> {
+    // Members only, resolving the dependencies of an unreleased workspace needs the network
+    let metadata = MetadataCommand::new()
+        .manifest_path(snapshot.workspace_path.join("Cargo.toml"))
+        .no_deps()
+        .exec()?;
+
+    let root_package = metadata
+        .packages
+        .iter()
+        .find(|package| package.name == root_crate_name)
+        .ok_or_else(|| format!("`{}` is not a member of the workspace", root_crate_name))?;
+    let mut version = Version::parse(&root_package.version.to_string())?;
+    if version.pre.is_empty() {
+        version.pre = "dev".parse()?;
+    }
+    version.build = snapshot.build_metadata().parse()?;
+
+    let mut shaders = Vec::new();
+    for package in &metadata.packages {
+        if !package_filter(&package.name) {
+            continue;
+        }
+
+        let crate_path = package.manifest_path.parent().unwrap().as_std_path();
+        let source_crate = SourceCrate {
+            name: package.name.clone(),
+            version: version.to_string(),
+            repository: package.repository.clone(),
+            path_in_repository: crate_path
+                .strip_prefix(&metadata.workspace_root)?
+                .to_path_buf(),
+            commit: Some(snapshot.commit.clone()),
+        };
+
+        shaders.extend(read_shaders(
+            crate_path,
+            &source_crate,
+            source_links,
+            |source| source,
+        )?);
+    }
+
+    Ok((version, shaders))
+}
+
+// Collects all `.wgsl` files below `crate_path`, `fix` patches sources that are known to be broken
+fn read_shaders(
+    crate_path: &Path,
+    source_crate: &SourceCrate,
+    source_links: &SourceLinkProvider,
+    fix: impl Fn(String) -> String,
+) -> Result<Vec<ShaderSource>, Box<dyn std::error::Error>> {
+    let mut shaders = Vec::new();
+
+    let mut dirs = vec![crate_path.to_path_buf()];
+    while let Some(parent) = dirs.pop() {
+        for entry in fs::read_dir(parent)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.is_file() && path.extension() == Some("wgsl".as_ref()) {
+                let source = fs::read_to_string(&path)?;
+                let shader_defs = find_defs(&source);
+
+                // Lines are counted before patching, the links point to the published source
+                let local = path.strip_prefix(crate_path)?;
+                let source_url = source_links.url(source_crate, local, None)?;
+                let mut item_source_urls = HashMap::new();
+                for (name, lines) in find_declarations(&source) {
+                    let url = source_links.url(source_crate, local, Some(lines))?;
+                    item_source_urls.insert(name, url);
                 }
+                let local_page = (*source_links == SourceLinkProvider::Local)
+                    .then(|| (source_crate.local_page(local), source.clone()));
+
+                let source = fix(source);
+
+                shaders.push(ShaderSource {
+                    path,
+                    source,
+                    shader_defs,
+                    source_url,
+                    item_source_urls,
+                    local_page,
+                });
             }
         }
     }
@@ -124,6 +223,23 @@ fn crates_io_download_url(name: &str, version: &Version) -> String {
     format!("https://static.crates.io/crates/{name}/{name}-{version}.crate")
 }
 
+// The `#define_import_path` of a shader, under which other shaders import it
+pub fn find_import_path(source: &str) -> Option<String> {
+    fn import_path_regex() -> &'static Regex {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        fn init() -> Regex {
+            Regex::new(r"^\s*#\s*define_import_path\s+([\w:]+)").unwrap()
+        }
+        RE.get_or_init(init)
+    }
+
+    source.lines().find_map(|line| {
+        import_path_regex()
+            .captures(line)
+            .map(|caps| caps.get(1).unwrap().as_str().to_string())
+    })
+}
+
 fn find_defs(source: &str) -> HashSet<String> {
     fn ifdef_regex() -> &'static Regex {
         static RE: OnceLock<Regex> = OnceLock::new();
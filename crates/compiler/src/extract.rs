@@ -0,0 +1,164 @@
+use crate::{common, download::ShaderSource};
+use docs::{
+    Constant, Doc, Function, GlobalVariable, Ident, IndexMap, Override, ShaderDefValue, Struct,
+    Version,
+};
+use std::collections::HashMap;
+
+// Maps the names of items visible in a module to the path of the module defining them
+pub type DefPaths = HashMap<String, Vec<String>>;
+
+pub struct Shader {
+    pub import_path: String,
+    pub source: ShaderSource,
+    pub imports: Vec<Import>,
+}
+
+pub struct Import {
+    pub path: Vec<String>,
+    // Items imported by name, empty if the whole module is imported
+    pub items: Vec<String>,
+}
+
+// Everything that depends on the naga and naga_oil versions, implemented once per backend. The
+// traversal of the modules and the assembly of the doc is shared by all backends.
+pub trait NagaAdapter: Sized {
+    // A composed naga module together with the layouts of its types
+    type Module: Sync;
+
+    // Marker naga_oil puts into the names of items imported from other modules
+    const DECORATION: &'static str;
+
+    // Preprocesses the sources and adds all modules with an import path to a new composer
+    fn compose(
+        shader_sources: Vec<ShaderSource>,
+    ) -> Result<(Self, Vec<Shader>), Box<dyn std::error::Error>>;
+
+    fn make_naga_module(
+        &mut self,
+        shader: &Shader,
+        shader_def_values: &IndexMap<String, ShaderDefValue>,
+    ) -> Result<Self::Module, Box<dyn std::error::Error>>;
+
+    // Names of the structs, constants and overrides, including the imported ones
+    fn item_names(module: &Self::Module) -> Vec<String>;
+
+    fn constants(module: &Self::Module, def_paths: &DefPaths) -> Vec<Constant>;
+
+    // Overrides are only supported by naga 0.20 and later
+    fn overrides(_module: &Self::Module, _def_paths: &DefPaths) -> Vec<Override> {
+        Vec::new()
+    }
+
+    fn global_variables(module: &Self::Module, def_paths: &DefPaths) -> Vec<GlobalVariable>;
+
+    fn structs(module: &Self::Module, def_paths: &DefPaths) -> Vec<Struct>;
+
+    fn functions(module: &Self::Module, def_paths: &DefPaths) -> Vec<Function>;
+}
+
+pub fn compile<A: NagaAdapter>(
+    root_crate_name: &str,
+    root_crate_version: Version,
+    shader_def_values: IndexMap<String, ShaderDefValue>,
+    shader_sources: Vec<ShaderSource>,
+) -> Result<Doc, Box<dyn std::error::Error>> {
+    let mut doc = common::doc_new(
+        root_crate_name.to_string(),
+        root_crate_version,
+        shader_def_values.clone(),
+    );
+
+    // The modules are made one after another, naga_oil's composer needs `&mut` for every module
+    // and can't be shared or cloned. It caches the imports it composed, so a composer per worker
+    // would compose them again for every worker. Only the extraction is spread across workers,
+    // versions are compiled in parallel by the caller.
+    let (mut adapter, mut shaders) = A::compose(shader_sources)?;
+    shaders.sort_by(|a, b| a.import_path.cmp(&b.import_path));
+    let naga_modules = shaders
+        .iter()
+        .map(|shader| adapter.make_naga_module(shader, &shader_def_values))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    common::compile_parallel(&mut doc, |doc, worker, workers| {
+        let modules = shaders.iter().zip(&naga_modules);
+        for (shader, naga_module) in modules.skip(worker).step_by(workers) {
+            let (module_path, module) = common::find_or_create_module(doc, &shader.import_path);
+
+            module
+                .shader_defs
+                .extend(shader.source.shader_defs.iter().cloned());
+            module.source_url = Some(shader.source.source_url.clone());
+            module
+                .imports
+                .extend(shader.imports.iter().map(|import| import.path.clone()));
+
+            let mut def_paths = DefPaths::new();
+            for import in &shader.imports {
+                for item in &import.items {
+                    def_paths.insert(item.clone(), import.path.clone());
+                }
+            }
+            for name in A::item_names(naga_module) {
+                if !name.contains(A::DECORATION) {
+                    def_paths.insert(name, module_path.clone());
+                }
+            }
+
+            let is_local = |name: &Ident| !is_imported::<A>(name);
+            module.constants.extend(
+                A::constants(naga_module, &def_paths)
+                    .into_iter()
+                    .filter(|constant| is_local(&constant.name)),
+            );
+            module.overrides.extend(
+                A::overrides(naga_module, &def_paths)
+                    .into_iter()
+                    .filter(|override_| is_local(&override_.name)),
+            );
+            module.global_variables.extend(
+                A::global_variables(naga_module, &def_paths)
+                    .into_iter()
+                    .filter(|var| is_local(&var.name)),
+            );
+            module.structs.extend(
+                A::structs(naga_module, &def_paths)
+                    .into_iter()
+                    .filter(|struct_| is_local(&struct_.name)),
+            );
+            module.functions.extend(
+                A::functions(naga_module, &def_paths)
+                    .into_iter()
+                    .filter(|function| is_local(&function.name)),
+            );
+
+            // Declarations removed by shader defs aren't linked
+            let names = module
+                .constants
+                .iter()
+                .map(|constant| &constant.name)
+                .chain(module.overrides.iter().map(|override_| &override_.name))
+                .chain(module.global_variables.iter().map(|var| &var.name))
+                .chain(module.structs.iter().map(|struct_| &struct_.name))
+                .chain(module.functions.iter().map(|function| &function.name))
+                .map(Ident::to_string)
+                .collect::<Vec<_>>();
+            for name in names {
+                if let Some(url) = shader.source.item_source_urls.get(&name) {
+                    module.item_source_urls.insert(name, url.clone());
+                }
+            }
+        }
+
+        Ok(())
+    })?;
+
+    Ok(doc)
+}
+
+fn is_imported<A: NagaAdapter>(name: &Ident) -> bool {
+    match name {
+        Ident::Named(name) => name.contains(A::DECORATION),
+        Ident::Unnamed => false,
+    }
+}
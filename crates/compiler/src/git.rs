@@ -0,0 +1,170 @@
+use flate2::read::GzDecoder;
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tar::Archive;
+
+// Written next to the extracted tree, archives only carry the commit time in their entries
+const COMMIT_TIME_FILE: &str = ".commit_time";
+
+// A commit of a git repository, extracted into the cache
+pub struct Snapshot {
+    pub workspace_path: PathBuf,
+    pub commit: String,
+    // Seconds since the unix epoch
+    pub commit_time: u64,
+}
+
+impl Snapshot {
+    // Abbreviated commit, as used in the version
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+
+    // Build metadata of the version, the commit time comes first so that snapshots of the same
+    // version order chronologically, e.g. `20261018093000.abc1234`
+    pub fn build_metadata(&self) -> String {
+        format!(
+            "{}.{}",
+            utc_timestamp(self.commit_time),
+            self.short_commit()
+        )
+    }
+}
+
+// Extracts `revision` of a repository checkout, or the tree of an archive (`.tar.gz`, `.tgz` or
+// `.tar`) of a commit. Archives can't be resolved, so `revision` has to be their commit hash.
+// Nothing is fetched, so it works offline.
+pub fn extract_snapshot(
+    source_path: &Path,
+    revision: &str,
+    cache_path: &Path,
+) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    let commit = if source_path.is_dir() {
+        let commit = git(
+            source_path,
+            &["rev-parse", "--verify", &format!("{}^{{commit}}", revision)],
+        )?;
+        String::from_utf8(commit)?.trim().to_string()
+    } else if revision.len() >= 7 && revision.chars().all(|c| c.is_ascii_hexdigit()) {
+        revision.to_ascii_lowercase()
+    } else {
+        return Err(format!(
+            "the revision of an archive must be its commit hash, got `{}`",
+            revision
+        )
+        .into());
+    };
+
+    // Trees extracted before the commit time was recorded are extracted again
+    let extract_path = cache_path.join(format!("git@{}", commit));
+    if !extract_path.join(COMMIT_TIME_FILE).exists() {
+        fs::remove_dir_all(&extract_path).ok();
+        // Extracted next to the destination and renamed, an interrupted run leaves no partial tree
+        let temp_path = cache_path.join(format!("git@{}.tmp", commit));
+        fs::remove_dir_all(&temp_path).ok();
+        fs::create_dir_all(&temp_path)?;
+
+        let commit_time = if source_path.is_dir() {
+            let tree = git(source_path, &["archive", "--format=tar", &commit])?;
+            unpack(tree.as_slice(), &temp_path)?
+        } else {
+            let file = File::open(source_path)?;
+            let reader: Box<dyn Read> = if source_path.extension() == Some("tar".as_ref()) {
+                Box::new(file)
+            } else {
+                Box::new(GzDecoder::new(file))
+            };
+            unpack(reader, &temp_path)?
+        };
+        fs::write(temp_path.join(COMMIT_TIME_FILE), commit_time.to_string())?;
+
+        fs::rename(&temp_path, &extract_path)?;
+    }
+
+    Ok(Snapshot {
+        workspace_path: workspace_path(&extract_path)?,
+        commit,
+        commit_time: fs::read_to_string(extract_path.join(COMMIT_TIME_FILE))?
+            .trim()
+            .parse()?,
+    })
+}
+
+// Unpacks an archive of a commit and returns the commit time, which git and forges set as the
+// modification time of every entry
+fn unpack(reader: impl Read, path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut commit_time = 0;
+    for entry in Archive::new(reader).entries()? {
+        let mut entry = entry?;
+        commit_time = commit_time.max(entry.header().mtime()?);
+        entry.unpack_in(path)?;
+    }
+    Ok(commit_time)
+}
+
+// `YYYYMMDDhhmmss` in UTC, from the civil date algorithm of Howard Hinnant
+fn utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let secs_of_day = secs % 86400;
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+// Forges put the tree of an archive into a directory named after the repository and commit
+fn workspace_path(extract_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if extract_path.join("Cargo.toml").exists() {
+        return Ok(extract_path.to_path_buf());
+    }
+
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(extract_path)? {
+        let path = entry?.path();
+        if path.join("Cargo.toml").exists() {
+            dirs.push(path);
+        }
+    }
+    if dirs.len() != 1 {
+        return Err(format!("no cargo workspace found in {}", extract_path.display()).into());
+    }
+    Ok(dirs.remove(0))
+}
+
+fn git(repository_path: &Path, args: &[&str]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository_path)
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(output.stdout)
+}
@@ -1,6 +1,9 @@
 mod common;
 mod download;
+mod extract;
+mod git;
 mod post_process;
+mod source_link;
 
 #[cfg(feature = "backend_v0_22")]
 mod backend_v0_22;
@@ -32,9 +35,17 @@ mod backend_v0_10;
 #[cfg(feature = "backend_v0_08")]
 mod backend_v0_08;
 
-use docs::{Doc, IndexMap, ShaderDefValue, Version};
+use data_encoding::HEXLOWER;
+use docs::{Doc, ExternCrate, IndexMap, ShaderDefValue, Version};
+use sha2::{Digest, Sha256};
 
-use std::path::Path;
+pub use git::{extract_snapshot, Snapshot};
+pub use source_link::{ForgeKind, SourceLinkProvider};
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CompilerBackend {
@@ -61,6 +72,63 @@ pub enum CompilerBackend {
 }
 
 impl CompilerBackend {
+    // The backend matching the naga_oil version a bevy release depends on
+    pub fn for_bevy(version: &Version) -> Option<Self> {
+        match (version.major, version.minor) {
+            #[cfg(feature = "backend_v0_22")]
+            (0, 19) => Some(CompilerBackend::V0_22),
+            #[cfg(feature = "backend_v0_20")]
+            (0, 18) => Some(CompilerBackend::V0_20),
+            #[cfg(feature = "backend_v0_19")]
+            (0, 17) => Some(CompilerBackend::V0_19),
+            #[cfg(feature = "backend_v0_17")]
+            (0, 16) => Some(CompilerBackend::V0_17),
+            #[cfg(feature = "backend_v0_16")]
+            (0, 15) => Some(CompilerBackend::V0_16),
+            #[cfg(feature = "backend_v0_14")]
+            (0, 14) => Some(CompilerBackend::V0_14),
+            #[cfg(feature = "backend_v0_13")]
+            (0, 13) => Some(CompilerBackend::V0_13),
+            #[cfg(feature = "backend_v0_10")]
+            (0, 12) => Some(CompilerBackend::V0_10),
+            #[cfg(feature = "backend_v0_08")]
+            (0, 11) => Some(CompilerBackend::V0_08),
+            _ => None,
+        }
+    }
+
+    // The backend compiling with naga_oil `0.{minor}`
+    pub fn for_naga_oil(minor: u64) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|backend| backend.naga_oil_minor() == minor)
+    }
+
+    fn all() -> Vec<Self> {
+        vec![
+            #[cfg(feature = "backend_v0_22")]
+            CompilerBackend::V0_22,
+            #[cfg(feature = "backend_v0_20")]
+            CompilerBackend::V0_20,
+            #[cfg(feature = "backend_v0_19")]
+            CompilerBackend::V0_19,
+            #[cfg(feature = "backend_v0_17")]
+            CompilerBackend::V0_17,
+            #[cfg(feature = "backend_v0_16")]
+            CompilerBackend::V0_16,
+            #[cfg(feature = "backend_v0_14")]
+            CompilerBackend::V0_14,
+            #[cfg(feature = "backend_v0_13")]
+            CompilerBackend::V0_13,
+            #[cfg(feature = "backend_v0_11")]
+            CompilerBackend::V0_11,
+            #[cfg(feature = "backend_v0_10")]
+            CompilerBackend::V0_10,
+            #[cfg(feature = "backend_v0_08")]
+            CompilerBackend::V0_08,
+        ]
+    }
+
     fn naga_oil_minor(self) -> u64 {
         match self {
             #[cfg(feature = "backend_v0_22")]
@@ -94,55 +162,292 @@ pub fn compile(
     shader_def_values: IndexMap<String, ShaderDefValue>,
     cache_path: &Path,
     backend: CompilerBackend,
+    source_links: &SourceLinkProvider,
 ) -> Result<Doc, Box<dyn std::error::Error>> {
+    load(
+        root_crate_name,
+        root_crate_version,
+        package_filter,
+        shader_def_values,
+        cache_path,
+        backend,
+        source_links,
+    )?
+    .compile()
+}
+
+// Downloaded inputs of a compilation. Split from `compile` so that callers can check `hash`
+// and skip compiling versions whose inputs didn't change.
+pub struct Input {
+    root_crate_name: String,
+    root_crate_version: Version,
+    shader_def_values: IndexMap<String, ShaderDefValue>,
+    backend: CompilerBackend,
+    shader_sources: Vec<download::ShaderSource>,
+}
+
+pub fn load(
+    root_crate_name: &str,
+    root_crate_version: Version,
+    package_filter: impl Fn(&str) -> bool,
+    shader_def_values: IndexMap<String, ShaderDefValue>,
+    cache_path: &Path,
+    backend: CompilerBackend,
+    source_links: &SourceLinkProvider,
+) -> Result<Input, Box<dyn std::error::Error>> {
     let shader_sources = download::download_shaders(
         root_crate_name,
         &root_crate_version,
         package_filter,
         cache_path,
         backend,
+        source_links,
     )?;
 
-    // CompileFn type is necessary to avoid compiler error if no backend is enabled
-    let compile: CompileFn = match backend {
-        #[cfg(feature = "backend_v0_22")]
-        CompilerBackend::V0_22 => backend_v0_22::compile,
-        #[cfg(feature = "backend_v0_20")]
-        CompilerBackend::V0_20 => backend_v0_20::compile,
-        #[cfg(feature = "backend_v0_19")]
-        CompilerBackend::V0_19 => backend_v0_19::compile,
-        #[cfg(feature = "backend_v0_17")]
-        CompilerBackend::V0_17 => backend_v0_17::compile,
-        #[cfg(feature = "backend_v0_16")]
-        CompilerBackend::V0_16 => backend_v0_16::compile,
-        #[cfg(feature = "backend_v0_14")]
-        CompilerBackend::V0_14 => backend_v0_14::compile,
-        #[cfg(feature = "backend_v0_13")]
-        CompilerBackend::V0_13 => backend_v0_13::compile,
-        #[cfg(feature = "backend_v0_11")]
-        CompilerBackend::V0_11 => backend_v0_11::compile,
-        #[cfg(feature = "backend_v0_10")]
-        CompilerBackend::V0_10 => backend_v0_10::compile,
-        #[cfg(feature = "backend_v0_08")]
-        CompilerBackend::V0_08 => backend_v0_08::compile,
-    };
+    Ok(Input::new(
+        root_crate_name,
+        root_crate_version,
+        shader_def_values,
+        backend,
+        shader_sources,
+    ))
+}
+
+// Like `load`, but reads the shaders of crates that are already on disk instead of downloading
+// them. The crates are named after their directories and share the root crate's version.
+pub fn load_local(
+    root_crate_name: &str,
+    root_crate_version: Version,
+    crate_paths: &[&Path],
+    shader_def_values: IndexMap<String, ShaderDefValue>,
+    backend: CompilerBackend,
+    source_links: &SourceLinkProvider,
+) -> Result<Input, Box<dyn std::error::Error>> {
+    let mut shader_sources = Vec::new();
+    for crate_path in crate_paths {
+        let name = crate_path
+            .file_name()
+            .ok_or_else(|| format!("invalid crate path: {}", crate_path.display()))?;
+        shader_sources.extend(download::local_shaders(
+            crate_path,
+            &name.to_string_lossy(),
+            &root_crate_version,
+            source_links,
+        )?);
+    }
 
-    let mut doc = compile(
+    Ok(Input::new(
         root_crate_name,
         root_crate_version,
-        shader_def_values.clone(),
+        shader_def_values,
+        backend,
         shader_sources,
-    )?;
+    ))
+}
+
+// Like `load`, but reads the workspace of a git snapshot from `extract_snapshot` instead of
+// downloading a release. Forge links point to the commit instead of a tag.
+pub fn load_git(
+    root_crate_name: &str,
+    snapshot: &Snapshot,
+    package_filter: impl Fn(&str) -> bool,
+    shader_def_values: IndexMap<String, ShaderDefValue>,
+    backend: CompilerBackend,
+    source_links: &SourceLinkProvider,
+) -> Result<Input, Box<dyn std::error::Error>> {
+    let (root_crate_version, shader_sources) =
+        download::snapshot_shaders(root_crate_name, snapshot, package_filter, source_links)?;
+
+    Ok(Input::new(
+        root_crate_name,
+        root_crate_version,
+        shader_def_values,
+        backend,
+        shader_sources,
+    ))
+}
+
+// Parses a shader def given on the command line: `NAME` is a boolean def, `NAME=8u` an unsigned
+// and `NAME=-1` a signed integer
+pub fn parse_shader_def(def: &str) -> Result<(String, ShaderDefValue), Box<dyn std::error::Error>> {
+    let Some((key, value)) = def.split_once('=') else {
+        return Ok((def.to_string(), ShaderDefValue::Bool(true)));
+    };
+
+    let value = match value {
+        "true" => ShaderDefValue::Bool(true),
+        "false" => ShaderDefValue::Bool(false),
+        value => match value.strip_suffix('u') {
+            Some(value) => ShaderDefValue::UInt(value.parse()?),
+            None => ShaderDefValue::Int(value.parse()?),
+        },
+    };
+    Ok((key.to_string(), value))
+}
+
+impl Input {
+    fn new(
+        root_crate_name: &str,
+        root_crate_version: Version,
+        shader_def_values: IndexMap<String, ShaderDefValue>,
+        backend: CompilerBackend,
+        mut shader_sources: Vec<download::ShaderSource>,
+    ) -> Self {
+        // Directory listing order is not stable, the hash and the compiled output should be
+        shader_sources.sort_by(|a, b| a.source_url.cmp(&b.source_url));
+
+        Self {
+            root_crate_name: root_crate_name.to_string(),
+            root_crate_version,
+            shader_def_values,
+            backend,
+            shader_sources,
+        }
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.root_crate_version
+    }
+
+    // File of every module by its import path
+    pub fn source_paths(&self) -> IndexMap<String, PathBuf> {
+        let mut paths = self
+            .shader_sources
+            .iter()
+            .filter_map(|shader_source| {
+                download::find_import_path(&shader_source.source)
+                    .map(|import_path| (import_path, shader_source.path.clone()))
+            })
+            .collect::<IndexMap<_, _>>();
+        paths.sort_keys();
+        paths
+    }
 
-    post_process::post_process(&mut doc);
+    // Source of every module by its import path, as used by wgsl-analyzer's `customImports`
+    pub fn custom_imports(&self) -> IndexMap<String, String> {
+        let mut imports = self
+            .shader_sources
+            .iter()
+            .filter_map(|shader_source| {
+                download::find_import_path(&shader_source.source)
+                    .map(|import_path| (import_path, shader_source.source.clone()))
+            })
+            .collect::<IndexMap<_, _>>();
+        imports.sort_keys();
+        imports
+    }
+
+    // Hex encoded hash of everything the compiled doc depends on
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        let mut field = |value: &[u8]| {
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        };
+
+        field(env!("SOURCE_HASH").as_bytes());
+        field(format!("{:?}", self.backend).as_bytes());
+        field(self.root_crate_name.as_bytes());
+        field(self.root_crate_version.to_string().as_bytes());
+        for (key, value) in &self.shader_def_values {
+            field(key.as_bytes());
+            field(value.to_string().as_bytes());
+        }
+        for shader_source in &self.shader_sources {
+            field(shader_source.source_url.as_bytes());
+            field(shader_source.source.as_bytes());
+        }
+
+        HEXLOWER.encode(&hasher.finalize())
+    }
+
+    pub fn compile(self) -> Result<Doc, Box<dyn std::error::Error>> {
+        let input_hash = self.hash();
+
+        // CompileFn type is necessary to avoid compiler error if no backend is enabled
+        let compile: CompileFn = match self.backend {
+            #[cfg(feature = "backend_v0_22")]
+            CompilerBackend::V0_22 => extract::compile::<backend_v0_22::Adapter>,
+            #[cfg(feature = "backend_v0_20")]
+            CompilerBackend::V0_20 => extract::compile::<backend_v0_20::Adapter>,
+            #[cfg(feature = "backend_v0_19")]
+            CompilerBackend::V0_19 => extract::compile::<backend_v0_19::Adapter>,
+            #[cfg(feature = "backend_v0_17")]
+            CompilerBackend::V0_17 => extract::compile::<backend_v0_17::Adapter>,
+            #[cfg(feature = "backend_v0_16")]
+            CompilerBackend::V0_16 => extract::compile::<backend_v0_16::Adapter>,
+            #[cfg(feature = "backend_v0_14")]
+            CompilerBackend::V0_14 => extract::compile::<backend_v0_14::Adapter>,
+            #[cfg(feature = "backend_v0_13")]
+            CompilerBackend::V0_13 => extract::compile::<backend_v0_13::Adapter>,
+            #[cfg(feature = "backend_v0_11")]
+            CompilerBackend::V0_11 => extract::compile::<backend_v0_11::Adapter>,
+            #[cfg(feature = "backend_v0_10")]
+            CompilerBackend::V0_10 => extract::compile::<backend_v0_10::Adapter>,
+            #[cfg(feature = "backend_v0_08")]
+            CompilerBackend::V0_08 => extract::compile::<backend_v0_08::Adapter>,
+        };
+
+        let local_pages = self
+            .shader_sources
+            .iter()
+            .filter_map(|shader_source| shader_source.local_page.clone())
+            .collect();
+
+        let mut doc = compile(
+            &self.root_crate_name,
+            self.root_crate_version,
+            self.shader_def_values.clone(),
+            self.shader_sources,
+        )?;
+        doc.input_hash = Some(input_hash);
+        doc.sources = local_pages;
 
-    for key in shader_def_values.keys() {
-        if !doc.root.shader_defs.contains(key) {
-            println!("Warning: shader def `{}` was provided but not used", key);
+        post_process::post_process(&mut doc);
+
+        for key in self.shader_def_values.keys() {
+            if !doc.root.shader_defs.contains(key) {
+                eprintln!("Warning: shader def `{}` was provided but not used", key);
+            }
         }
+
+        Ok(doc)
     }
+}
+
+// Removes the modules that are already documented by `dependency` from `doc`, links to them
+// resolve into the dependency's docs instead.
+pub fn link_dependency(doc: &mut Doc, dependency: &Doc) {
+    let names = dependency
+        .root
+        .modules
+        .iter()
+        .map(|module| module.name.clone())
+        .collect::<HashSet<_>>();
+
+    doc.root.modules.retain(|module| {
+        if !names.contains(&module.name) {
+            return true;
+        }
+
+        doc.extern_crates.insert(
+            module.name.clone(),
+            ExternCrate {
+                name: dependency.root.name.clone(),
+                version: dependency.version.clone(),
+            },
+        );
+        false
+    });
 
-    Ok(doc)
+    // Shader defs of the root are collected from its modules
+    doc.root.shader_defs = doc
+        .root
+        .modules
+        .iter()
+        .flat_map(|module| module.shader_defs.iter().cloned())
+        .collect();
+    doc.root.shader_defs.sort();
 }
 
 type CompileFn = fn(
@@ -21,6 +21,9 @@ fn post_process_module(module: &mut Module) -> IndexSet<String> {
     // Sort constants
     module.constants.sort_by(|a, b| a.name.cmp(&b.name));
 
+    // Sort overrides
+    module.overrides.sort_by(|a, b| a.name.cmp(&b.name));
+
     // Sort global variables
     module.global_variables.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -30,8 +33,15 @@ fn post_process_module(module: &mut Module) -> IndexSet<String> {
     // Sort functions
     module.functions.sort_by(|a, b| a.name.cmp(&b.name));
 
+    // Sort item source urls
+    module.item_source_urls.sort_keys();
+
     // Sort shader defs
     module.shader_defs.sort();
 
+    // Sort imports
+    module.imports.sort();
+    module.imports.dedup();
+
     module.shader_defs.clone()
 }
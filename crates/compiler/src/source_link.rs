@@ -0,0 +1,281 @@
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+// Where the `source` links of modules and items point to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SourceLinkProvider {
+    // The source view of the published crate
+    #[default]
+    DocsRs,
+    // Blob pages of the forge hosting the crate's `repository`. Releases link to the tag
+    // `tag_pattern` with `{name}` and `{version}` replaced, git snapshots to their commit.
+    Forge {
+        kind: ForgeKind,
+        tag_pattern: String,
+    },
+    // Pages of the sources generated next to the docs
+    Local,
+}
+
+// Forges differ in where they put blob pages and how they anchor line ranges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+}
+
+// A crate whose shaders are linked
+pub(crate) struct SourceCrate {
+    pub name: String,
+    pub version: String,
+    pub repository: Option<String>,
+    // Directory of the crate in its repository
+    pub path_in_repository: PathBuf,
+    // Commit of a git snapshot, released crates are linked by tag
+    pub commit: Option<String>,
+}
+
+impl SourceCrate {
+    // Published crates record where they were packaged from in `.cargo_vcs_info.json`, crates
+    // packaged before cargo 1.58 didn't and are assumed to be at the root of their repository
+    pub fn path_in_vcs(crate_path: &Path) -> PathBuf {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| Regex::new(r#""path_in_vcs"\s*:\s*"([^"]*)""#).unwrap());
+
+        let Ok(vcs_info) = fs::read_to_string(crate_path.join(".cargo_vcs_info.json")) else {
+            return PathBuf::new();
+        };
+        re.captures(&vcs_info)
+            .map(|caps| PathBuf::from(&caps[1]))
+            .unwrap_or_default()
+    }
+
+    // Path of a source page below the directory of the version, without the extension
+    pub fn local_page(&self, path: &Path) -> String {
+        join_url("src".to_string(), &Path::new(&self.name).join(path))
+    }
+}
+
+impl SourceLinkProvider {
+    // Link to a file of a crate, `path` is relative to the crate. `lines` (1-based, inclusive)
+    // narrows it down to a declaration. Local links are relative to the directory of the version.
+    pub(crate) fn url(
+        &self,
+        crate_: &SourceCrate,
+        path: &Path,
+        lines: Option<(usize, usize)>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let mut url = match self {
+            SourceLinkProvider::DocsRs => {
+                if crate_.commit.is_some() {
+                    return Err(format!(
+                        "`{}` is a git snapshot, docs.rs only has published crates",
+                        crate_.name
+                    )
+                    .into());
+                }
+                let base = format!(
+                    "https://docs.rs/crate/{}/{}/source",
+                    crate_.name, crate_.version
+                );
+                join_url(base, path)
+            }
+            SourceLinkProvider::Forge { kind, tag_pattern } => {
+                let repository = crate_.repository.as_ref().ok_or_else(|| {
+                    format!(
+                        "`{}` has no `repository` to link the sources to",
+                        crate_.name
+                    )
+                })?;
+                let revision = match &crate_.commit {
+                    Some(commit) => commit.clone(),
+                    None => tag_pattern
+                        .replace("{name}", &crate_.name)
+                        .replace("{version}", &crate_.version),
+                };
+                let base = format!("{}/{}", blob_base(*kind, repository), revision);
+                join_url(base, &crate_.path_in_repository.join(path))
+            }
+            SourceLinkProvider::Local => format!("{}.html", crate_.local_page(path)),
+        };
+
+        if let Some((start, end)) = lines {
+            let gitlab = matches!(
+                self,
+                SourceLinkProvider::Forge {
+                    kind: ForgeKind::GitLab,
+                    ..
+                }
+            );
+            if start == end {
+                url.push_str(&format!("#L{}", start));
+            } else if gitlab {
+                url.push_str(&format!("#L{}-{}", start, end));
+            } else {
+                url.push_str(&format!("#L{}-L{}", start, end));
+            }
+        }
+        Ok(url)
+    }
+}
+
+// GitLab puts the pages of a repository below `-`
+fn blob_base(kind: ForgeKind, repository: &str) -> String {
+    let repository = repository.trim_end_matches('/').trim_end_matches(".git");
+    match kind {
+        ForgeKind::GitHub => format!("{}/blob", repository),
+        ForgeKind::GitLab => format!("{}/-/blob", repository),
+    }
+}
+
+fn join_url(mut url: String, path: &Path) -> String {
+    for segment in path.components() {
+        url.push('/');
+        url.push_str(&segment.as_os_str().to_string_lossy());
+    }
+    url
+}
+
+// Line ranges (1-based, inclusive) of the top level declarations by name. The compiled module
+// doesn't keep spans, so they are found in the text. The first declaration of a name wins,
+// `#ifdef` branches may declare it again.
+pub(crate) fn find_declarations(source: &str) -> HashMap<String, (usize, usize)> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:@\w+(?:\([^)]*\))?\s*)*(?:fn|struct|const|override|var(?:<[^>]*>)?)\s+([A-Za-z_]\w*)")
+            .unwrap()
+    });
+
+    let mut declarations = HashMap::new();
+    let mut depth = 0usize;
+    let mut open = None;
+    for (idx, code) in strip_comments(source).lines().enumerate() {
+        if depth == 0 && open.is_none() {
+            if let Some(caps) = re.captures(code) {
+                open = Some((caps[1].to_string(), idx + 1));
+            }
+        }
+
+        for c in code.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        // Functions and structs end with their closing brace, everything else with a semicolon
+        if depth == 0 && (code.contains('}') || code.contains(';')) {
+            if let Some((name, start)) = open.take() {
+                declarations.entry(name).or_insert((start, idx + 1));
+            }
+        }
+    }
+    declarations
+}
+
+// Replaces comments with spaces, line breaks are kept so that lines still match the source. Block
+// comments nest in WGSL.
+fn strip_comments(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut block_depth = 0usize;
+    let mut in_line_comment = false;
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        if c == '\n' {
+            in_line_comment = false;
+            code.push(c);
+        } else if in_line_comment {
+            code.push(' ');
+        } else if c == '/' && next == Some('*') {
+            chars.next();
+            block_depth += 1;
+            code.push_str("  ");
+        } else if block_depth > 0 && c == '*' && next == Some('/') {
+            chars.next();
+            block_depth -= 1;
+            code.push_str("  ");
+        } else if block_depth > 0 {
+            code.push(' ');
+        } else if c == '/' && next == Some('/') {
+            in_line_comment = true;
+            code.push(' ');
+        } else {
+            code.push(c);
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crate_(commit: Option<&str>) -> SourceCrate {
+        SourceCrate {
+            name: "bevy_pbr".to_string(),
+            version: "0.18.0".to_string(),
+            repository: Some("https://example.com/bevyengine/bevy.git".to_string()),
+            path_in_repository: PathBuf::from("crates/bevy_pbr"),
+            commit: commit.map(str::to_string),
+        }
+    }
+
+    fn forge(kind: ForgeKind) -> SourceLinkProvider {
+        SourceLinkProvider::Forge {
+            kind,
+            tag_pattern: "v{version}".to_string(),
+        }
+    }
+
+    #[test]
+    fn forge_urls() {
+        let path = Path::new("src/render/pbr.wgsl");
+        assert_eq!(
+            forge(ForgeKind::GitHub)
+                .url(&crate_(None), path, Some((3, 8)))
+                .unwrap(),
+            "https://example.com/bevyengine/bevy/blob/v0.18.0/crates/bevy_pbr/src/render/pbr.wgsl#L3-L8"
+        );
+        assert_eq!(
+            forge(ForgeKind::GitLab)
+                .url(&crate_(None), path, Some((3, 8)))
+                .unwrap(),
+            "https://example.com/bevyengine/bevy/-/blob/v0.18.0/crates/bevy_pbr/src/render/pbr.wgsl#L3-8"
+        );
+        assert_eq!(
+            forge(ForgeKind::GitLab)
+                .url(&crate_(Some("abc1234")), path, Some((5, 5)))
+                .unwrap(),
+            "https://example.com/bevyengine/bevy/-/blob/abc1234/crates/bevy_pbr/src/render/pbr.wgsl#L5"
+        );
+        assert!(SourceLinkProvider::DocsRs
+            .url(&crate_(Some("abc1234")), path, None)
+            .is_err());
+    }
+
+    #[test]
+    fn declarations_skip_comments() {
+        let source = "\
+/* fn commented_out() {
+   } */
+fn shade() -> f32 { /* { */
+    // }
+    return 1.0;
+}
+
+/* nested /* block */ struct Hidden {} */
+const SCALE: f32 = 2.0; // fn trailing() {}
+";
+        let declarations = find_declarations(source);
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(declarations["shade"], (3, 6));
+        assert_eq!(declarations["SCALE"], (9, 9));
+    }
+}
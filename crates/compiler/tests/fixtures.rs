@@ -0,0 +1,484 @@
+// Compiles the fixture crates with every enabled backend and compares the resulting doc, the
+// generated pages and the generated Rust types against the snapshots in `tests/snapshots`. Set
+// `UPDATE_SNAPSHOTS=1` to accept changes.
+#![cfg(any(
+    feature = "backend_v0_22",
+    feature = "backend_v0_20",
+    feature = "backend_v0_19",
+    feature = "backend_v0_17",
+    feature = "backend_v0_16",
+    feature = "backend_v0_14",
+    feature = "backend_v0_13",
+    feature = "backend_v0_11",
+    feature = "backend_v0_10",
+    feature = "backend_v0_08",
+))]
+
+use compiler::{CompilerBackend, SourceLinkProvider};
+use docs::{Doc, IndexMap, ShaderDefValue, Version};
+use generator::{MemorySink, RustStyle};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Every backend compiles `fixture_core` and `fixture_render`, the listed crates are added where
+// the backend's naga supports them: `fixture_tuning` declares a scalar override (naga_oil 0.14
+// and later), `fixture_compute` sizes arrays with one (naga_oil 0.19 and later).
+macro_rules! backend_tests {
+    ($($feature:literal => $name:ident: $backend:ident, [$($fixture:literal),*],)*) => {
+        $(
+            #[cfg(feature = $feature)]
+            #[test]
+            fn $name() {
+                check_backend(stringify!($name), CompilerBackend::$backend, &[$($fixture),*]);
+            }
+        )*
+    };
+}
+
+backend_tests! {
+    "backend_v0_22" => backend_v0_22: V0_22, ["fixture_tuning", "fixture_compute"],
+    "backend_v0_20" => backend_v0_20: V0_20, ["fixture_tuning", "fixture_compute"],
+    "backend_v0_19" => backend_v0_19: V0_19, ["fixture_tuning", "fixture_compute"],
+    "backend_v0_17" => backend_v0_17: V0_17, ["fixture_tuning"],
+    "backend_v0_16" => backend_v0_16: V0_16, ["fixture_tuning"],
+    "backend_v0_14" => backend_v0_14: V0_14, ["fixture_tuning"],
+    "backend_v0_13" => backend_v0_13: V0_13, [],
+    "backend_v0_11" => backend_v0_11: V0_11, [],
+    "backend_v0_10" => backend_v0_10: V0_10, [],
+    "backend_v0_08" => backend_v0_08: V0_08, [],
+}
+
+fn check_backend(name: &str, backend: CompilerBackend, fixtures: &[&str]) {
+    let mut doc = compile_fixtures(backend, fixtures);
+
+    // The hash changes with every change to the compiler, it's covered by the incremental build
+    doc.input_hash = None;
+    check_snapshot(&format!("{name}.doc.txt"), &format!("{doc:#?}\n"));
+
+    let sink = MemorySink::new();
+    generator::generate(&doc, &sink).unwrap();
+    let version_path = Path::new("fixtures/0.1.0");
+    let pages = sink
+        .into_files()
+        .into_iter()
+        .filter_map(|(path, contents)| {
+            let path = path.strip_prefix(version_path).ok()?.to_path_buf();
+            Some((path, String::from_utf8(contents).ok()?))
+        })
+        .collect();
+    check_snapshot(&format!("{name}.html.txt"), &pages_content(pages));
+
+    let book_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("fixtures_{name}_md"));
+    fs::remove_dir_all(&book_path).ok();
+    generator::generate_markdown(&doc, &book_path).unwrap();
+    check_snapshot(
+        &format!("{name}.md.txt"),
+        &pages_content(read_pages(&book_path)),
+    );
+
+    let rust_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("fixtures_{name}_rs"));
+    let mut rust = String::new();
+    for (style, file_name) in [
+        (RustStyle::Encase, "encase.rs"),
+        (RustStyle::Bytemuck, "bytemuck.rs"),
+    ] {
+        generator::generate_rust(&doc, &rust_path.join(file_name), style).unwrap();
+        rust.push_str(&format!("==> {file_name} <==\n"));
+        rust.push_str(&fs::read_to_string(rust_path.join(file_name)).unwrap());
+    }
+    check_snapshot(&format!("{name}.rs.txt"), &rust);
+}
+
+fn compile_fixtures(backend: CompilerBackend, fixtures: &[&str]) -> Doc {
+    compile_fixtures_linked(backend, fixtures, &SourceLinkProvider::DocsRs)
+}
+
+fn compile_fixtures_linked(
+    backend: CompilerBackend,
+    fixtures: &[&str],
+    source_links: &SourceLinkProvider,
+) -> Doc {
+    let fixtures_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    let mut crate_paths = vec![
+        fixtures_path.join("fixture_core"),
+        fixtures_path.join("fixture_render"),
+    ];
+    crate_paths.extend(fixtures.iter().map(|fixture| fixtures_path.join(fixture)));
+
+    compiler::load_local(
+        "fixtures",
+        Version::new(0, 1, 0),
+        &crate_paths.iter().map(PathBuf::as_path).collect::<Vec<_>>(),
+        shader_def_values(),
+        backend,
+        source_links,
+    )
+    .unwrap()
+    .compile()
+    .unwrap()
+}
+
+fn shader_def_values() -> IndexMap<String, ShaderDefValue> {
+    IndexMap::from([
+        ("SHADOWS".to_string(), ShaderDefValue::Bool(true)),
+        ("MAX_STEPS".to_string(), ShaderDefValue::UInt(8)),
+    ])
+}
+
+// Local source links point to pages generated next to the docs, items to their lines
+#[cfg(feature = "backend_v0_20")]
+#[test]
+fn local_sources() {
+    let doc = compile_fixtures_linked(CompilerBackend::V0_20, &[], &SourceLinkProvider::Local);
+    let types_page = "src/fixture_core/src/types.wgsl";
+    assert!(doc.sources.contains_key(types_page));
+
+    let sink = MemorySink::new();
+    generator::generate(&doc, &sink).unwrap();
+    let files = sink.into_files();
+    let version_path = Path::new("fixtures/0.1.0");
+    let source_page =
+        String::from_utf8(files[&version_path.join(format!("{types_page}.html"))].clone()).unwrap();
+    assert!(source_page.contains(r#"id="L1""#));
+
+    // Item pages are next to the index of their module
+    let module_path = version_path.join("fixtures/fixture_core/types");
+    let (name, url) = doc.root.modules[0]
+        .modules
+        .iter()
+        .find(|module| module.name == "types")
+        .and_then(|module| module.item_source_urls.first())
+        .unwrap();
+    assert!(url.starts_with(&format!("{types_page}.html#L")));
+    let item_page = files
+        .iter()
+        .find(|(path, _)| {
+            path.parent() == Some(&module_path)
+                && path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .ends_with(&format!(".{name}.html"))
+        })
+        .map(|(_, contents)| String::from_utf8(contents.clone()).unwrap())
+        .unwrap();
+    assert!(item_page.contains(&format!(r#"href="../../../{url}""#)));
+}
+
+// `fixture_app` is documented on its own and imports `fixture_core`, which links to the fixtures
+#[cfg(feature = "backend_v0_20")]
+#[test]
+fn cross_crate_links() {
+    let fixtures = compile_fixtures(CompilerBackend::V0_20, &[]);
+
+    let fixtures_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut app = compiler::load_local(
+        "app",
+        Version::new(0, 3, 0),
+        &[
+            &fixtures_path.join("fixture_core"),
+            &fixtures_path.join("fixture_app"),
+        ],
+        shader_def_values(),
+        CompilerBackend::V0_20,
+        &SourceLinkProvider::DocsRs,
+    )
+    .unwrap()
+    .compile()
+    .unwrap();
+    compiler::link_dependency(&mut app, &fixtures);
+    assert_eq!(app.root.modules.len(), 1);
+    assert_eq!(app.root.modules[0].name, "fixture_app");
+    assert_eq!(app.extern_crates["fixture_core"].name, "fixtures");
+
+    let sink = MemorySink::new();
+    generator::generate(&fixtures, &sink).unwrap();
+    generator::generate(&app, &sink).unwrap();
+    let files = sink.into_files();
+
+    let glow_path = Path::new("app/0.3.0/app/fixture_app/glow");
+    let glow_page = String::from_utf8(files[&glow_path.join("struct.Glow.html")].clone()).unwrap();
+    let href = glow_page
+        .split('"')
+        .find(|part| part.ends_with("struct.Light.html"))
+        .unwrap();
+
+    // The link resolves to the page of the fixtures
+    let mut light_path = glow_path.to_path_buf();
+    for segment in href.split('/') {
+        match segment {
+            ".." => assert!(light_path.pop()),
+            _ => light_path.push(segment),
+        }
+    }
+    assert_eq!(
+        light_path,
+        Path::new("fixtures/0.1.0/fixtures/fixture_core/types/struct.Light.html")
+    );
+    assert!(files.contains_key(&light_path));
+}
+
+// The docset indexes the items in its SQLite database, anchors them on the module pages for Dash's
+// table of contents and is offered by the feed
+#[cfg(feature = "backend_v0_20")]
+#[test]
+fn docset() {
+    let doc = compile_fixtures(CompilerBackend::V0_20, &[]);
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures_docset");
+    fs::remove_dir_all(&path).ok();
+    generator::generate_docset(&doc, &path, "https://example.com/docsets/").unwrap();
+
+    let docset_path = path.join("0.1.0/fixtures.docset");
+    let connection =
+        rusqlite::Connection::open(docset_path.join("Contents/Resources/docSet.dsidx")).unwrap();
+    let mut select = connection
+        .prepare("SELECT name, type, path FROM searchIndex")
+        .unwrap();
+    let rows = select
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<Vec<(String, String, String)>, _>>()
+        .unwrap();
+    for (name, type_, page) in [
+        (
+            "types",
+            "Module",
+            "fixtures/0.1.0/fixtures/fixture_core/types/index.html",
+        ),
+        (
+            "Light",
+            "Struct",
+            "fixtures/0.1.0/fixtures/fixture_core/types/struct.Light.html",
+        ),
+        (
+            "CAST_SHADOWS",
+            "Constant",
+            "fixtures/0.1.0/fixtures/fixture_core/types/const.CAST_SHADOWS.html",
+        ),
+    ] {
+        assert!(
+            rows.contains(&(name.to_string(), type_.to_string(), page.to_string())),
+            "{name} is missing from {rows:?}"
+        );
+    }
+    let documents_path = docset_path.join("Contents/Resources/Documents");
+    for (_, _, page) in &rows {
+        assert!(documents_path.join(page).is_file(), "{page} doesn't exist");
+    }
+
+    let module_page = fs::read_to_string(
+        documents_path.join("fixtures/0.1.0/fixtures/fixture_core/types/index.html"),
+    )
+    .unwrap();
+    assert!(module_page.contains(
+        r#"<a name="//apple_ref/cpp/Struct/Light" class="dashAnchor"></a><a class="struct" href="struct.Light.html">"#
+    ));
+
+    assert!(path.join("0.1.0/fixtures.tgz").is_file());
+    assert_eq!(
+        fs::read_to_string(path.join("fixtures.xml")).unwrap(),
+        "<entry>\n    <version>0.1.0</version>\n    \
+         <url>https://example.com/docsets/0.1.0/fixtures.tgz</url>\n</entry>\n"
+    );
+}
+
+// The fixtures as a git workspace, documented from the checkout and from an archive of the commit
+#[cfg(feature = "backend_v0_20")]
+#[test]
+fn git_snapshot() {
+    use compiler::ForgeKind;
+    use std::process::Command;
+
+    let tmp_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("git_snapshot");
+    fs::remove_dir_all(&tmp_path).ok();
+    let repository_path = tmp_path.join("repository");
+
+    let fixtures_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let manifest = |name: &str| {
+        format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.2.0\"\nedition = \"2021\"\n\
+            repository = \"https://github.com/example/fixtures.git\"\n"
+        )
+    };
+    fs::create_dir_all(repository_path.join("src")).unwrap();
+    fs::write(
+        repository_path.join("Cargo.toml"),
+        manifest("fixtures") + "\n[workspace]\nmembers = [\"crates/*\"]\n",
+    )
+    .unwrap();
+    fs::write(repository_path.join("src/lib.rs"), "").unwrap();
+    for name in ["fixture_core", "fixture_render"] {
+        let crate_path = repository_path.join("crates").join(name);
+        fs::create_dir_all(crate_path.join("src")).unwrap();
+        fs::write(crate_path.join("Cargo.toml"), manifest(name)).unwrap();
+        fs::write(crate_path.join("src/lib.rs"), "").unwrap();
+        for entry in fs::read_dir(fixtures_path.join(name).join("src")).unwrap() {
+            let path = entry.unwrap().path();
+            fs::copy(
+                &path,
+                crate_path.join("src").join(path.file_name().unwrap()),
+            )
+            .unwrap();
+        }
+    }
+
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&repository_path)
+            .args([
+                "-c",
+                "user.name=fixtures",
+                "-c",
+                "user.email=fixtures@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "fixtures"]);
+    let commit = git(&["rev-parse", "HEAD"]);
+    let archive_path = tmp_path.join("fixtures.tar.gz");
+    git(&[
+        "archive",
+        "--format=tar.gz",
+        &format!("--prefix=fixtures-{commit}/"),
+        "-o",
+        archive_path.to_str().unwrap(),
+        "HEAD",
+    ]);
+
+    for (source_path, revision) in [(&repository_path, "HEAD"), (&archive_path, &commit)] {
+        let cache_path = tmp_path
+            .join("cache")
+            .join(source_path.file_name().unwrap());
+        let snapshot = compiler::extract_snapshot(source_path, revision, &cache_path).unwrap();
+        let doc = compiler::load_git(
+            "fixtures",
+            &snapshot,
+            |name| name.starts_with("fixture"),
+            shader_def_values(),
+            CompilerBackend::V0_20,
+            &SourceLinkProvider::Forge {
+                kind: ForgeKind::GitHub,
+                tag_pattern: "v{version}".to_string(),
+            },
+        )
+        .unwrap()
+        .compile()
+        .unwrap();
+
+        // Snapshots are ordered by the time of their commit, then told apart by its hash
+        assert_eq!(doc.version.pre.as_str(), "dev");
+        let (commit_time, short_commit) = doc.version.build.as_str().split_once('.').unwrap();
+        assert_eq!(commit_time.len(), 14);
+        assert!(commit_time.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(short_commit, &commit[..7]);
+
+        let mut source_urls = Vec::new();
+        collect_source_urls(&doc.root, &mut source_urls);
+        let blob_url = format!("https://github.com/example/fixtures/blob/{commit}/");
+        assert!(source_urls
+            .iter()
+            .all(|source_url| source_url.starts_with(&blob_url)));
+        assert!(source_urls.contains(&format!("{blob_url}crates/fixture_core/src/types.wgsl")));
+        assert!(source_urls
+            .iter()
+            .any(|source_url| source_url.contains(".wgsl#L")));
+    }
+}
+
+#[cfg(feature = "backend_v0_20")]
+fn collect_source_urls(module: &docs::Module, source_urls: &mut Vec<String>) {
+    source_urls.extend(module.source_url.clone());
+    source_urls.extend(module.item_source_urls.values().cloned());
+    for inner in &module.modules {
+        collect_source_urls(inner, source_urls);
+    }
+}
+
+// Files below `path` with their path relative to it
+fn read_pages(path: &Path) -> BTreeMap<PathBuf, String> {
+    let mut pages = BTreeMap::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let page = entry.unwrap().path();
+            if page.is_dir() {
+                dirs.push(page);
+            } else {
+                let source = fs::read_to_string(&page).unwrap();
+                pages.insert(page.strip_prefix(path).unwrap().to_path_buf(), source);
+            }
+        }
+    }
+    pages
+}
+
+// Content of every generated page without the surrounding layout, which is the same everywhere,
+// the WGSL interface stubs and the Markdown pages
+fn pages_content(pages: BTreeMap<PathBuf, String>) -> String {
+    let mut content = String::new();
+    for (page, source) in pages {
+        if !["html", "wgsl", "md"]
+            .iter()
+            .any(|extension| page.extension() == Some(extension.as_ref()))
+        {
+            continue;
+        }
+
+        let (start, end) = match source.find(r#"<div id="innerContent">"#) {
+            Some(start) => (start, source[start..].find("<script").unwrap() + start),
+            None => (0, source.len()),
+        };
+
+        content.push_str(&format!("==> {} <==\n", page.display()));
+        for line in source[start..end].lines() {
+            if !line.trim().is_empty() {
+                content.push_str(line.trim_end());
+                content.push('\n');
+            }
+        }
+    }
+    content
+}
+
+fn check_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+
+    // Recorded snapshots still have to be reviewed before committing them
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        println!("Recorded snapshot {}", path.display());
+        return;
+    }
+
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!(
+            "snapshot {} is missing, run with UPDATE_SNAPSHOTS=1 to record it",
+            path.display()
+        );
+    };
+    if expected != actual {
+        let line = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(expected, actual)| expected != actual)
+            .unwrap_or(expected.lines().count().min(actual.lines().count()));
+        panic!(
+            "snapshot {} differs starting at line {}, rerun with UPDATE_SNAPSHOTS=1 to accept",
+            path.display(),
+            line + 1
+        );
+    }
+}
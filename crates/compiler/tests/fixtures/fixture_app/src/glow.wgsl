@@ -0,0 +1,12 @@
+#define_import_path fixture_app::glow
+
+#import fixture_core::types
+
+struct Glow {
+    light: fixture_core::types::Light,
+    radius: f32,
+}
+
+fn glow_color(glow: Glow) -> vec4<f32> {
+    return glow.light.color * glow.light.intensity / glow.radius;
+}
@@ -0,0 +1,20 @@
+#define_import_path fixture_compute::reduce
+
+// naga_oil doesn't remap the overrides sizing arrays when it composes a module, they keep their
+// handle from the parsed source. With a single override both handles are the same.
+@id(0) override WORKGROUP_SIZE: u32 = 64u;
+
+var<workgroup> partial_sums: array<f32, WORKGROUP_SIZE>;
+var<private> remaining: u32 = WORKGROUP_SIZE;
+
+@group(0) @binding(0) var inputs: binding_array<texture_2d<f32>, WORKGROUP_SIZE>;
+
+fn reduce(index: u32) {
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride /= 2u) {
+        if index < stride {
+            partial_sums[index] += partial_sums[index + stride];
+        }
+        remaining = stride;
+        workgroupBarrier();
+    }
+}
@@ -0,0 +1,17 @@
+#define_import_path fixture_core::bindings
+
+#import fixture_core::types
+
+@group(0) @binding(0) var<uniform> lights: fixture_core::types::Lights;
+@group(0) @binding(1) var color_texture: texture_2d<f32>;
+@group(0) @binding(2) var color_sampler: sampler;
+#ifdef SHADOWS
+@group(0) @binding(3) var shadow_map: texture_depth_2d;
+@group(0) @binding(4) var shadow_sampler: sampler_comparison;
+#endif
+
+@group(1) @binding(0) var<storage, read> instances: fixture_core::types::Instances;
+@group(1) @binding(1) var<storage, read_write> counters: array<atomic<u32>>;
+
+var<private> scratch: vec4<f32>;
+var<workgroup> shared_data: array<f32, 64>;
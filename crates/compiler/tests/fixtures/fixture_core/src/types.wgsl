@@ -0,0 +1,25 @@
+#define_import_path fixture_core::types
+
+const MAX_LIGHTS: u32 = 4u;
+const AMBIENT: vec3<f32> = vec3<f32>(0.1, 0.1, 0.2);
+const CAST_SHADOWS: bool = true;
+
+struct Light {
+    color: vec4<f32>,
+    direction: vec3<f32>,
+    intensity: f32,
+}
+
+struct Lights {
+    count: u32,
+    data: array<Light, 4>,
+}
+
+struct Instance {
+    model: mat4x4<f32>,
+    tint: vec4<f32>,
+}
+
+struct Instances {
+    data: array<Instance>,
+}
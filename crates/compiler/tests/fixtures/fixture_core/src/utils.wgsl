@@ -0,0 +1,29 @@
+#define_import_path fixture_core::utils
+
+const STEPS: u32 = #{MAX_STEPS}u;
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn saturate_in_place(value: ptr<function, f32>) {
+    *value = clamp(*value, 0.0, 1.0);
+}
+
+fn sum(values: ptr<function, array<f32, 4>>) -> f32 {
+    var total = 0.0;
+    for (var i = 0; i < 4; i += 1) {
+        total += (*values)[i];
+    }
+    return total;
+}
+
+#ifdef SHADOWS
+fn shadow_bias(normal_dot_light: f32) -> f32 {
+    return max(0.05 * (1.0 - normal_dot_light), 0.005);
+}
+#else
+fn shadow_bias(normal_dot_light: f32) -> f32 {
+    return 0.0;
+}
+#endif
@@ -0,0 +1,46 @@
+#define_import_path fixture_render::mesh
+
+#import fixture_core::types
+#import fixture_core::bindings
+#import fixture_core::utils
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) instance_index: u32,
+    @location(2) @interpolate(perspective, centroid) world_position: vec3<f32>,
+}
+
+fn instance(index: u32) -> fixture_core::types::Instance {
+    return fixture_core::bindings::instances.data[index];
+}
+
+fn transform(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let model = instance(vertex.instance_index).model;
+    out.clip_position = model * vec4<f32>(vertex.position, 1.0);
+    out.uv = vertex.uv;
+    out.instance_index = vertex.instance_index;
+    out.world_position = (model * vec4<f32>(vertex.position, 1.0)).xyz;
+    return out;
+}
+
+fn shade(in: VertexOutput, front_facing: bool) -> vec4<f32> {
+    var color = textureSample(
+        fixture_core::bindings::color_texture,
+        fixture_core::bindings::color_sampler,
+        in.uv,
+    );
+    var brightness = fixture_core::utils::luminance(color.rgb);
+    fixture_core::utils::saturate_in_place(&brightness);
+    if !front_facing {
+        brightness *= 0.5;
+    }
+    return vec4<f32>(color.rgb * brightness + fixture_core::types::AMBIENT, color.a);
+}
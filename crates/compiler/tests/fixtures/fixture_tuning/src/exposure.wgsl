@@ -0,0 +1,8 @@
+#define_import_path fixture_tuning::exposure
+
+// A scalar override that doesn't size any array, unlike the one of `fixture_compute`
+@id(1) override EXPOSURE: f32 = 1.5;
+
+fn expose(color: vec3<f32>) -> vec3<f32> {
+    return color * EXPOSURE;
+}
@@ -3,21 +3,39 @@ use std::fmt;
 pub use indexmap::{IndexMap, IndexSet};
 pub use semver::Version;
 
+#[derive(Debug)]
 pub struct Doc {
     pub version: Version,
     pub root: Module,
     pub compiled_with: IndexMap<String, ShaderDefValue>,
+    // Top level modules that are documented as part of another crate
+    pub extern_crates: IndexMap<String, ExternCrate>,
+    // Hash of the inputs the doc was compiled from, used to skip regenerating unchanged versions
+    pub input_hash: Option<String>,
+    // Sources by the path of their page below the version, when the source links are local
+    pub sources: IndexMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct ExternCrate {
+    pub name: String,
+    pub version: Version,
 }
 
+#[derive(Debug)]
 pub struct Module {
     pub name: String,
     pub source_url: Option<String>,
+    // Links to the lines declaring the items, by item name
+    pub item_source_urls: IndexMap<String, String>,
     pub modules: Vec<Module>,
     pub constants: Vec<Constant>,
+    pub overrides: Vec<Override>,
     pub global_variables: Vec<GlobalVariable>,
     pub structs: Vec<Struct>,
     pub functions: Vec<Function>,
     pub shader_defs: IndexSet<String>,
+    pub imports: Vec<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -37,12 +55,22 @@ impl fmt::Display for ShaderDefValue {
     }
 }
 
+#[derive(Debug)]
 pub struct Constant {
     pub name: Ident,
     pub ty: Type,
     pub init: Expression,
 }
 
+#[derive(Debug)]
+pub struct Override {
+    pub name: Ident,
+    pub id: Option<u16>,
+    pub ty: Type,
+    pub init: Option<Expression>,
+}
+
+#[derive(Debug)]
 pub struct GlobalVariable {
     pub name: Ident,
     pub space: AddressSpace,
@@ -51,6 +79,7 @@ pub struct GlobalVariable {
     pub init: Option<Expression>,
 }
 
+#[derive(Debug)]
 pub enum AddressSpace {
     Function,
     Private,
@@ -89,25 +118,181 @@ impl fmt::Display for AddressSpace {
     }
 }
 
+#[derive(Debug)]
 pub struct ResourceBinding {
     pub group: u32,
     pub binding: u32,
 }
 
+#[derive(Debug)]
 pub enum Expression {
     Literal(Literal),
+    Constant {
+        name: String,
+        def_path: Option<Vec<String>>,
+    },
+    Override {
+        name: String,
+        def_path: Option<Vec<String>>,
+    },
+    ZeroValue(Type),
+    Compose {
+        ty: Type,
+        components: Vec<Expression>,
+    },
+    Splat {
+        size: u32,
+        value: Box<Expression>,
+    },
+    Unary {
+        op: UnaryOperator,
+        expr: Box<Expression>,
+    },
+    Binary {
+        op: BinaryOperator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    As {
+        expr: Box<Expression>,
+        scalar: String,
+        convert: bool,
+    },
     Unknown,
 }
 
+impl Expression {
+    pub fn is_compound(&self) -> bool {
+        matches!(self, Expression::Binary { .. })
+    }
+
+    // Operands of a unary operator also need parentheses when they start with an operator
+    // themselves, `--x` would be a decrement
+    pub fn is_compound_unary_operand(&self) -> bool {
+        match self {
+            Expression::Unary { .. } => true,
+            Expression::Literal(literal) => literal.is_negative(),
+            expr => expr.is_compound(),
+        }
+    }
+}
+
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn operand(f: &mut fmt::Formatter<'_>, expr: &Expression) -> fmt::Result {
+            if expr.is_compound() {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        }
+
         match self {
             Expression::Literal(literal) => write!(f, "{}", literal),
+            Expression::Constant { name, .. } => write!(f, "{}", name),
+            Expression::Override { name, .. } => write!(f, "{}", name),
+            Expression::ZeroValue(ty) => write!(f, "{}()", ty),
+            Expression::Compose { ty, components } => {
+                write!(f, "{}(", ty)?;
+                for (i, component) in components.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", component)?;
+                }
+                write!(f, ")")
+            }
+            Expression::Splat { size, value } => write!(f, "vec{}({})", size, value),
+            Expression::Unary { op, expr } if expr.is_compound_unary_operand() => {
+                write!(f, "{}({})", op, expr)
+            }
+            Expression::Unary { op, expr } => write!(f, "{}{}", op, expr),
+            Expression::Binary { op, left, right } => {
+                operand(f, left)?;
+                write!(f, " {} ", op)?;
+                operand(f, right)
+            }
+            Expression::As {
+                expr,
+                scalar,
+                convert,
+            } => {
+                if *convert {
+                    write!(f, "{}({})", scalar, expr)
+                } else {
+                    write!(f, "bitcast<{}>({})", scalar, expr)
+                }
+            }
             Expression::Unknown => write!(f, ".."),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryOperator {
+    Negate,
+    LogicalNot,
+    BitwiseNot,
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOperator::Negate => write!(f, "-"),
+            UnaryOperator::LogicalNot => write!(f, "!"),
+            UnaryOperator::BitwiseNot => write!(f, "~"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    ExclusiveOr,
+    InclusiveOr,
+    LogicalAnd,
+    LogicalOr,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOperator::Add => write!(f, "+"),
+            BinaryOperator::Subtract => write!(f, "-"),
+            BinaryOperator::Multiply => write!(f, "*"),
+            BinaryOperator::Divide => write!(f, "/"),
+            BinaryOperator::Modulo => write!(f, "%"),
+            BinaryOperator::Equal => write!(f, "=="),
+            BinaryOperator::NotEqual => write!(f, "!="),
+            BinaryOperator::Less => write!(f, "<"),
+            BinaryOperator::LessEqual => write!(f, "<="),
+            BinaryOperator::Greater => write!(f, ">"),
+            BinaryOperator::GreaterEqual => write!(f, ">="),
+            BinaryOperator::And => write!(f, "&"),
+            BinaryOperator::ExclusiveOr => write!(f, "^"),
+            BinaryOperator::InclusiveOr => write!(f, "|"),
+            BinaryOperator::LogicalAnd => write!(f, "&&"),
+            BinaryOperator::LogicalOr => write!(f, "||"),
+            BinaryOperator::ShiftLeft => write!(f, "<<"),
+            BinaryOperator::ShiftRight => write!(f, ">>"),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum Literal {
     F64(f64),
     F32(f32),
@@ -122,6 +307,18 @@ pub enum Literal {
     AbstractFloat(f64),
 }
 
+impl Literal {
+    pub fn is_negative(&self) -> bool {
+        match *self {
+            Literal::F64(value) | Literal::AbstractFloat(value) => value.is_sign_negative(),
+            Literal::F32(value) | Literal::F16(value) => value.is_sign_negative(),
+            Literal::I32(value) => value < 0,
+            Literal::I64(value) | Literal::AbstractInt(value) => value < 0,
+            Literal::U32(_) | Literal::U64(_) | Literal::Bool(_) => false,
+        }
+    }
+}
+
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -140,17 +337,26 @@ impl fmt::Display for Literal {
     }
 }
 
+#[derive(Debug)]
 pub struct Struct {
     pub name: Ident,
     pub members: Vec<StructMember>,
+    pub size: u32,
+    pub align: u32,
 }
 
+#[derive(Debug)]
 pub struct StructMember {
     pub name: Ident,
     pub ty: Type,
     pub binding: Option<Binding>,
+    pub offset: u32,
+    pub size: u32,
+    pub align: u32,
+    pub stride: Option<u32>,
 }
 
+#[derive(Debug)]
 pub enum Type {
     Named {
         name: String,
@@ -164,17 +370,64 @@ pub enum Type {
     },
     ArrayConstant(Box<Type>, Option<u32>),
     ArrayDynamic(Box<Type>),
+    ArrayPending {
+        base: Box<Type>,
+        size: String,
+        def_path: Option<Vec<String>>,
+    },
     BindingArrayConstant(Box<Type>, Option<u32>),
     BindingArrayDynamic(Box<Type>),
+    BindingArrayPending {
+        base: Box<Type>,
+        size: String,
+        def_path: Option<Vec<String>>,
+    },
     Unnamed,
 }
 
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn size(size: &Option<u32>) -> String {
+            match size {
+                Some(size) => size.to_string(),
+                None => "?".to_string(),
+            }
+        }
+
+        match self {
+            Type::Named { name, .. } => write!(f, "{}", name),
+            Type::Pointer(inner) => write!(f, "ptr<{}>", inner),
+            Type::PointerWithAddressSpace {
+                base,
+                address_space,
+                maybe_access,
+            } => match maybe_access {
+                Some(access) => write!(f, "ptr<{}, {}, {}>", address_space, base, access),
+                None => write!(f, "ptr<{}, {}>", address_space, base),
+            },
+            Type::ArrayConstant(inner, len) => write!(f, "array<{}, {}>", inner, size(len)),
+            Type::ArrayDynamic(inner) => write!(f, "array<{}>", inner),
+            Type::ArrayPending { base, size, .. } => write!(f, "array<{}, {}>", base, size),
+            Type::BindingArrayConstant(inner, len) => {
+                write!(f, "binding_array<{}, {}>", inner, size(len))
+            }
+            Type::BindingArrayDynamic(inner) => write!(f, "binding_array<{}>", inner),
+            Type::BindingArrayPending { base, size, .. } => {
+                write!(f, "binding_array<{}, {}>", base, size)
+            }
+            Type::Unnamed => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct Function {
     pub name: Ident,
     pub arguments: Vec<FunctionArgument>,
     pub ret: Option<Type>,
 }
 
+#[derive(Debug)]
 pub struct FunctionArgument {
     pub name: Ident,
     pub ty: Type,
@@ -208,6 +461,7 @@ impl fmt::Display for Ident {
     }
 }
 
+#[derive(Debug)]
 pub enum Binding {
     BuiltIn(BuiltIn),
     Location {
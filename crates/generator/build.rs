@@ -0,0 +1,5 @@
+// Everything the generated pages depend on besides the inputs, a change invalidates what was
+// generated before
+fn main() {
+    source_hash::emit(&["src", "templates", "../docs/src"]);
+}
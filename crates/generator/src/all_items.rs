@@ -1,5 +1,4 @@
-use docs::{Doc, Ident, Module};
-use serde::Serialize;
+use docs::{Doc, Ident, Module, Type};
 
 pub fn all_items(doc: &Doc) -> Vec<Item> {
     let mut items = Vec::new();
@@ -21,12 +20,29 @@ fn all_items_module(module: &Module, parent: &[String], items: &mut Vec<Item>) {
             path.clone(),
             inner.name.clone(),
             ItemKind::Module,
+            format!("mod {}", inner.name),
         ));
     }
 
     for item in &module.constants {
         if let Ident::Named(name) = &item.name {
-            items.push(Item::new(path.clone(), name.clone(), ItemKind::Constant));
+            items.push(Item::new(
+                path.clone(),
+                name.clone(),
+                ItemKind::Constant,
+                format!("const {}: {}", name, item.ty),
+            ));
+        }
+    }
+
+    for item in &module.overrides {
+        if let Ident::Named(name) = &item.name {
+            items.push(Item::new(
+                path.clone(),
+                name.clone(),
+                ItemKind::Override,
+                format!("override {}: {}", name, item.ty),
+            ));
         }
     }
 
@@ -36,55 +52,86 @@ fn all_items_module(module: &Module, parent: &[String], items: &mut Vec<Item>) {
                 path.clone(),
                 name.clone(),
                 ItemKind::GlobalVariable,
+                format!("var{} {}: {}", item.space, name, item.ty),
             ));
         }
     }
 
     for item in &module.structs {
         if let Ident::Named(name) = &item.name {
-            items.push(Item::new(path.clone(), name.clone(), ItemKind::Struct));
+            items.push(Item::new(
+                path.clone(),
+                name.clone(),
+                ItemKind::Struct,
+                format!("struct {}", name),
+            ));
         }
     }
 
     for item in &module.functions {
         if let Ident::Named(name) = &item.name {
-            items.push(Item::new(path.clone(), name.clone(), ItemKind::Function));
+            let arguments = item
+                .arguments
+                .iter()
+                .map(|arg| format!("{}: {}", arg.name, arg.ty))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let signature = match &item.ret {
+                Some(ret) => format!("fn {}({}) -> {}", name, arguments, ret),
+                None => format!("fn {}({})", name, arguments),
+            };
+            let mut function = Item::new(path.clone(), name.clone(), ItemKind::Function, signature);
+            function.inputs = Some(
+                item.arguments
+                    .iter()
+                    .map(|arg| normalize_type(&arg.ty))
+                    .collect(),
+            );
+            function.output = item.ret.as_ref().map(normalize_type);
+            items.push(function);
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Item {
-    path: Vec<String>,
-    name: String,
-    kind: ItemKind,
-    url: String,
+    pub path: Vec<String>,
+    pub name: String,
+    pub kind: ItemKind,
+    pub signature: String,
+    // Normalized argument and return types of functions, used by the type-based search
+    pub inputs: Option<Vec<String>>,
+    pub output: Option<String>,
 }
 
 impl Item {
-    fn new(path: Vec<String>, name: String, kind: ItemKind) -> Self {
-        let mut url = path.join("/");
-        match kind {
-            ItemKind::Module => url.push_str(&format!("/{}/index.html", name)),
-            ItemKind::Constant => url.push_str(&format!("/const.{}.html", name)),
-            ItemKind::GlobalVariable => url.push_str(&format!("/var.{}.html", name)),
-            ItemKind::Struct => url.push_str(&format!("/struct.{}.html", name)),
-            ItemKind::Function => url.push_str(&format!("/fn.{}.html", name)),
-        }
-
+    fn new(path: Vec<String>, name: String, kind: ItemKind, signature: String) -> Self {
         Self {
             path,
             name,
             kind,
-            url,
+            signature,
+            inputs: None,
+            output: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
-enum ItemKind {
+// Type names are displayed without their module path, so only case and whitespace are left
+fn normalize_type(ty: &Type) -> String {
+    ty.to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// The order defines the kind codes used in the search index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ItemKind {
     Module,
     Constant,
+    Override,
     GlobalVariable,
     Struct,
     Function,
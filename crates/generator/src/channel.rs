@@ -0,0 +1,133 @@
+use docs::Version;
+use std::cmp::Ordering;
+
+// Release channels, each one has an alias directory next to the versions that tracks its newest
+// version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Stable,
+    Prerelease,
+    Snapshot,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 3] = [Channel::Stable, Channel::Prerelease, Channel::Snapshot];
+
+    // Git snapshots carry their commit as build metadata, e.g. `0.20.0-dev+20261018093000.abc1234`
+    pub fn of(version: &Version) -> Self {
+        if !version.build.is_empty() {
+            Channel::Snapshot
+        } else if !version.pre.is_empty() {
+            Channel::Prerelease
+        } else {
+            Channel::Stable
+        }
+    }
+
+    pub fn alias(self) -> &'static str {
+        match self {
+            Channel::Stable => "latest",
+            Channel::Prerelease => "next",
+            Channel::Snapshot => "main",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Channel::Stable => "Stable",
+            Channel::Prerelease => "Prerelease",
+            Channel::Snapshot => "Snapshot",
+        }
+    }
+}
+
+// Orders newest first. Precedence ignores build metadata, so snapshots of the same version are
+// ordered by theirs, which starts with the commit time.
+pub fn newest_first(a: &Version, b: &Version) -> Ordering {
+    b.cmp_precedence(a).then_with(|| b.build.cmp(&a.build))
+}
+
+// The version every alias points to, `versions` are sorted by `newest_first`. Prereleases that are
+// older than the newest stable release don't get an alias, the release superseded them.
+pub fn aliases(versions: &[Version]) -> Vec<(Channel, Version)> {
+    let newest = |channel| {
+        versions
+            .iter()
+            .find(|version| Channel::of(version) == channel)
+    };
+    let stable = newest(Channel::Stable);
+
+    Channel::ALL
+        .into_iter()
+        .filter_map(|channel| {
+            let version = newest(channel)?;
+            if channel == Channel::Prerelease
+                && stable.is_some_and(|stable| stable.cmp_precedence(version) == Ordering::Greater)
+            {
+                return None;
+            }
+            Some((channel, version.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        let mut versions = versions
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+        versions.sort_by(newest_first);
+        versions
+    }
+
+    fn aliased(versions: &[Version]) -> Vec<(&'static str, String)> {
+        aliases(versions)
+            .into_iter()
+            .map(|(channel, version)| (channel.alias(), version.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_order_by_commit_time() {
+        let sorted = versions(&[
+            "0.20.0-dev+20261001120000.bbbbbbb",
+            "0.20.0-dev+20261018093000.aaaaaaa",
+            "0.20.0-dev+20260915000000.ccccccc",
+        ]);
+        assert_eq!(
+            aliased(&sorted),
+            [("main", "0.20.0-dev+20261018093000.aaaaaaa".to_string())]
+        );
+        assert_eq!(sorted[2].to_string(), "0.20.0-dev+20260915000000.ccccccc");
+    }
+
+    #[test]
+    fn every_channel_gets_its_newest_version() {
+        let sorted = versions(&[
+            "0.18.0",
+            "0.19.0-rc.1",
+            "0.19.0-rc.2",
+            "0.18.1",
+            "0.20.0-dev+20261018093000.aaaaaaa",
+        ]);
+        assert_eq!(
+            aliased(&sorted),
+            [
+                ("latest", "0.18.1".to_string()),
+                ("next", "0.19.0-rc.2".to_string()),
+                ("main", "0.20.0-dev+20261018093000.aaaaaaa".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn released_prereleases_lose_their_alias() {
+        let sorted = versions(&["0.19.0-rc.2", "0.19.0"]);
+        assert_eq!(aliased(&sorted), [("latest", "0.19.0".to_string())]);
+        assert!(aliased(&[]).is_empty());
+    }
+}
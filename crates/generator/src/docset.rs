@@ -0,0 +1,234 @@
+use crate::{
+    all_items::{Item, ItemKind},
+    channel::{self, Channel},
+};
+use docs::{Doc, Version};
+use flate2::{write::GzEncoder, Compression};
+use rusqlite::{params, Connection};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+// Writes `Info.plist`, the `searchIndex` and the table of contents anchors of a docset whose
+// pages are already generated below `Contents/Resources/Documents`
+pub fn write_docset(
+    doc: &Doc,
+    items: &[Item],
+    docset_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents_path = docset_path.join("Contents");
+    let resources_path = contents_path.join("Resources");
+    let documents_path = resources_path.join("Documents");
+
+    fs::write(contents_path.join("Info.plist"), info_plist(doc))?;
+    write_search_index(doc, items, &resources_path.join("docSet.dsidx"))?;
+    add_toc_anchors(doc, items, &documents_path)?;
+
+    Ok(())
+}
+
+// Packs the docset into `{root}.docset` inside a gzipped tarball, the format Dash feeds point to
+pub fn write_archive(
+    doc: &Doc,
+    docset_path: &Path,
+    archive_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let encoder = GzEncoder::new(File::create(archive_path)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.append_dir_all(format!("{}.docset", doc.root.name), docset_path)?;
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+// Writes `{root}.xml` pointing to the archive of the newest release below `path`
+pub fn write_feed(
+    root_name: &str,
+    path: &Path,
+    feed_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.path().join(format!("{}.tgz", root_name)).exists() {
+            if let Ok(version) = Version::parse(&entry.file_name().to_string_lossy()) {
+                versions.push(version);
+            }
+        }
+    }
+    versions.sort_by(channel::newest_first);
+
+    // Prereleases are only offered until there is a release
+    let Some(latest) = versions
+        .iter()
+        .find(|version| Channel::of(version) == Channel::Stable)
+        .or(versions.first())
+    else {
+        return Ok(());
+    };
+    let feed = format!(
+        "<entry>\n    <version>{}</version>\n    <url>{}/{}/{}.tgz</url>\n</entry>\n",
+        latest,
+        escape_xml(feed_url.trim_end_matches('/')),
+        latest,
+        escape_xml(root_name)
+    );
+    fs::write(path.join(format!("{}.xml", root_name)), feed)?;
+
+    Ok(())
+}
+
+fn info_plist(doc: &Doc) -> String {
+    let name = escape_xml(&doc.root.name);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>CFBundleIdentifier</key>
+    <string>{name}</string>
+    <key>CFBundleName</key>
+    <string>{name} {version}</string>
+    <key>DocSetPlatformFamily</key>
+    <string>{name}</string>
+    <key>isDashDocset</key>
+    <true/>
+    <key>isJavaScriptEnabled</key>
+    <true/>
+    <key>dashIndexFilePath</key>
+    <string>{name}/{version}/{name}/index.html</string>
+    <key>DashDocSetFamily</key>
+    <string>dashtoc</string>
+</dict>
+</plist>
+"#,
+        name = name,
+        version = doc.version
+    )
+}
+
+fn write_search_index(
+    doc: &Doc,
+    items: &[Item],
+    index_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut connection = Connection::open(index_path)?;
+    connection.execute_batch(
+        "CREATE TABLE searchIndex(id INTEGER PRIMARY KEY, name TEXT, type TEXT, path TEXT);
+         CREATE UNIQUE INDEX anchor ON searchIndex (name, type, path);",
+    )?;
+
+    let transaction = connection.transaction()?;
+    {
+        let mut insert = transaction
+            .prepare("INSERT OR IGNORE INTO searchIndex(name, type, path) VALUES (?1, ?2, ?3)")?;
+        for item in items {
+            insert.execute(params![
+                item.name,
+                entry_type(item.kind),
+                item_page(doc, item)
+            ])?;
+        }
+    }
+    transaction.commit()?;
+
+    Ok(())
+}
+
+// Marks every item in the lists of the module pages, Dash builds the table of contents from them
+fn add_toc_anchors(
+    doc: &Doc,
+    items: &[Item],
+    documents_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut pages = BTreeMap::<&[String], Vec<&Item>>::new();
+    for item in items {
+        pages.entry(&item.path).or_default().push(item);
+    }
+
+    for (path, items) in pages {
+        let page_path = path
+            .iter()
+            .fold(version_path(doc, documents_path), |page_path, segment| {
+                page_path.join(segment)
+            })
+            .join("index.html");
+        let mut page = fs::read_to_string(&page_path)?;
+
+        for item in items {
+            let link = match item.kind {
+                ItemKind::Module => {
+                    format!(r#"<a class="module" href="{}/index.html">"#, item.name)
+                }
+                kind => format!(
+                    r#"<a class="{}" href="{}.{}.html">"#,
+                    page_prefix(kind),
+                    page_prefix(kind),
+                    item.name
+                ),
+            };
+            let anchor = format!(
+                r#"<a name="//apple_ref/cpp/{}/{}" class="dashAnchor"></a>"#,
+                entry_type(item.kind),
+                item.name
+            );
+            if let Some(idx) = page.find(&link) {
+                page.insert_str(idx, &anchor);
+            }
+        }
+
+        fs::write(&page_path, page)?;
+    }
+
+    Ok(())
+}
+
+// Page of an item relative to the documents directory
+fn item_page(doc: &Doc, item: &Item) -> String {
+    let file_name = match item.kind {
+        ItemKind::Module => format!("{}/index.html", item.name),
+        kind => format!("{}.{}.html", page_prefix(kind), item.name),
+    };
+    format!(
+        "{}/{}/{}/{}",
+        doc.root.name,
+        doc.version,
+        item.path.join("/"),
+        file_name
+    )
+}
+
+fn version_path(doc: &Doc, documents_path: &Path) -> PathBuf {
+    documents_path
+        .join(&doc.root.name)
+        .join(doc.version.to_string())
+}
+
+fn entry_type(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Module => "Module",
+        ItemKind::Constant | ItemKind::Override => "Constant",
+        ItemKind::GlobalVariable => "Variable",
+        ItemKind::Struct => "Struct",
+        ItemKind::Function => "Function",
+    }
+}
+
+fn page_prefix(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Module => "mod",
+        ItemKind::Constant => "const",
+        ItemKind::Override => "override",
+        ItemKind::GlobalVariable => "var",
+        ItemKind::Struct => "struct",
+        ItemKind::Function => "fn",
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
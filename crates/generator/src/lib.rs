@@ -1,70 +1,256 @@
 mod all_items;
+mod channel;
+mod docset;
+mod markdown;
+mod publish;
+mod render;
+mod rust;
+mod search_index;
+mod sink;
+mod wgsl;
+
+pub use channel::Channel;
+pub use publish::Retention;
+pub use rust::RustStyle;
+pub use sink::{ArchiveFormat, ArchiveSink, FsSink, MemorySink, OutputSink};
 
 use askama::Template;
 use docs::{
-    Binding, BuiltIn, Constant, Doc, Function, GlobalVariable, Interpolation, Module, Sampling,
-    Struct, Type, Version,
+    Constant, Doc, Expression, Function, GlobalVariable, IndexMap, Module, Override, Struct, Type,
+    Version,
 };
-use serde_json::Value;
+use render::{
+    display_address_space, display_binding, module_bindings, struct_layout, BindingRow, LayoutRow,
+    LinkKind, Markup,
+};
+use serde_json::{json, Value};
 use std::{
-    cmp::Ordering,
     collections::HashSet,
-    fs::{self, File},
-    ops::Deref,
-    path::Path,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{self, AtomicUsize},
+    thread,
 };
 
-pub fn generate(doc: &Doc, base_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let base_path = base_path.join(&doc.root.name);
+pub fn generate(doc: &Doc, sink: &dyn OutputSink) -> Result<(), Box<dyn std::error::Error>> {
+    generate_all(std::slice::from_ref(doc), sink, Retention::All)
+}
 
-    // Prepare directories
-    fs::create_dir_all(&base_path)?;
+// Generates several docs at once, the versions are generated in parallel and the shared files
+// (`common.js` of every crate and the landing page) are written once at the end. Versions are
+// staged and only published when all of them succeeded, published versions that `retention`
+// doesn't keep are removed afterwards.
+pub fn generate_all(
+    docs: &[Doc],
+    sink: &dyn OutputSink,
+    retention: Retention,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut roots = IndexMap::<&str, Vec<&Doc>>::new();
+    for doc in docs {
+        roots.entry(&doc.root.name).or_default().push(doc);
+    }
 
-    // Versions
-    let existing_versions = existing_versions(&base_path)?;
-    let is_latest =
-        existing_versions
-            .iter()
-            .all(|version| match doc.version.cmp_precedence(version) {
-                Ordering::Equal | Ordering::Greater => true,
-                Ordering::Less => false,
-            });
-    let all_versions = {
-        let mut versions = existing_versions.clone();
-        versions.insert(doc.version.clone());
-
-        let mut versions = versions.into_iter().collect::<Vec<_>>();
-        versions.sort_by(|a, b| a.cmp_precedence(b).reverse());
-
-        versions
-    };
+    let mut jobs = Vec::new();
+    let mut all_versions = Vec::new();
+    for (root, docs) in &roots {
+        let base_path = PathBuf::from(root);
+        publish::recover(sink, &base_path)?;
+
+        // Versions
+        let mut versions = existing_versions(sink, &base_path)?;
+        let mut batch = HashSet::new();
+        for doc in docs {
+            if !batch.insert(&doc.version) {
+                return Err(format!("{} {} is generated twice", root, doc.version).into());
+            }
+            versions.insert(doc.version.clone());
+        }
+
+        let versions = retention.retain(versions);
+        let aliases = channel::aliases(&versions);
+
+        for doc in docs {
+            // Versions that wouldn't be kept aren't generated at all
+            if !versions.contains(&doc.version) {
+                continue;
+            }
 
-    // Gen docs
-    gen_doc(doc, false, &base_path)?;
-    if is_latest {
-        gen_doc(doc, true, &base_path)?;
+            jobs.push((*doc, None, base_path.clone()));
+            for (channel, version) in &aliases {
+                if version == &doc.version {
+                    jobs.push((*doc, Some(*channel), base_path.clone()));
+                }
+            }
+        }
+        all_versions.push((base_path, versions, aliases));
+    }
+
+    // Gen docs, every job writes into its own staging directory
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let handles = (0..workers.min(jobs.len()))
+            .map(|_| {
+                scope.spawn(|| loop {
+                    let Some((doc, alias, base_path)) =
+                        jobs.get(next.fetch_add(1, atomic::Ordering::Relaxed))
+                    else {
+                        return Ok::<(), String>(());
+                    };
+                    let staged_path =
+                        publish::staged_path(base_path, &version_dir_name(doc, *alias));
+                    gen_doc(sink, doc, *alias, &staged_path).map_err(|err| err.to_string())?;
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap())
+    })?;
+
+    // Publish docs
+    for (doc, alias, base_path) in &jobs {
+        publish::publish(sink, base_path, &version_dir_name(doc, *alias))?;
     }
 
     // Store versions
-    let mut common = load_common_json(&base_path)?;
-    common["versions"] = Value::Array(
-        all_versions
+    for (base_path, versions, aliases) in &all_versions {
+        publish::clean_up(sink, base_path)?;
+        publish::collect_garbage(sink, base_path, versions, aliases)?;
+        store_versions(sink, base_path, versions, aliases)?;
+    }
+
+    // Landing page listing all documented crates
+    gen_landing(sink)?;
+
+    Ok(())
+}
+
+fn gen_landing(sink: &dyn OutputSink) -> Result<(), Box<dyn std::error::Error>> {
+    let mut crates = Vec::new();
+    for name in sink.dirs(Path::new(""))? {
+        let path = Path::new(&name);
+        if sink.read(&path.join("common.js"))?.is_none() {
+            continue;
+        }
+
+        let common = load_common_json(sink, path)?;
+        let versions = common["versions"]
+            .as_array()
+            .map(|versions| {
+                versions
+                    .iter()
+                    .filter_map(|version| version.as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        // Crates link to the alias of their first channel, usually `latest`
+        let Some(entry) = common["aliases"][0][0]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| versions.first().cloned())
+        else {
+            continue;
+        };
+        crates.push((name, entry, versions));
+    }
+    crates.sort();
+
+    let template = LandingTemplate { crates: &crates };
+    sink.write(Path::new("index.html"), template.render()?.as_bytes())?;
+
+    Ok(())
+}
+
+// Writes the structs and constants of `doc` as a Rust module to `path`
+pub fn generate_rust(
+    doc: &Doc,
+    path: &Path,
+    style: RustStyle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, rust::doc_to_rust(doc, style))?;
+    Ok(())
+}
+
+// Writes a WGSL interface stub per shader module, at `{path}/{import path}.wgsl`
+pub fn generate_wgsl(doc: &Doc, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut result = Ok(());
+    wgsl::for_each_shader_module(&doc.root, &[], &mut |module_path, module| {
+        if result.is_err() {
+            return;
+        }
+
+        let file_path = module_path
             .iter()
-            .map(|version| Value::String(version.to_string()))
-            .collect(),
-    );
-    store_common_json(&base_path, &common)?;
+            .fold(path.to_path_buf(), |file_path, segment| {
+                file_path.join(segment)
+            })
+            .with_extension("wgsl");
+        result = file_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&file_path, wgsl::module_to_wgsl(doc, module_path, module)));
+    });
+    Ok(result?)
+}
 
+// Writes a Markdown page per module and a `SUMMARY.md`, so `path` can be the `src` directory of
+// an mdBook
+pub fn generate_markdown(doc: &Doc, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    for page in markdown::doc_to_markdown(doc) {
+        let file_path = path.join(&page.path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file_path, page.content)?;
+    }
     Ok(())
 }
 
-fn load_common_json(base_path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
-    let common_path = base_path.join("common.js");
-    let source = if common_path.exists() {
-        fs::read_to_string(&common_path)?
-    } else {
+// Packages a version as a Dash/Zeal docset at `{path}/{version}/{root}.docset`, archives it next to
+// the bundle and points the feed `{path}/{root}.xml` to the newest archive below `feed_url`
+pub fn generate_docset(
+    doc: &Doc,
+    path: &Path,
+    feed_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let version_path = path.join(doc.version.to_string());
+    let docset_path = version_path.join(format!("{}.docset", doc.root.name));
+    let sink = FsSink::new(docset_path.join("Contents/Resources/Documents"));
+    let base_path = Path::new(&doc.root.name);
+
+    fs::remove_dir_all(&docset_path).ok();
+
+    // The bundle holds a single version, so it's neither built as an alias nor stamped
+    gen_doc(&sink, doc, None, &base_path.join(doc.version.to_string()))?;
+    sink.remove_file(&base_path.join(doc.version.to_string()).join(BUILD_STAMP))?;
+
+    store_versions(&sink, base_path, std::slice::from_ref(&doc.version), &[])?;
+
+    docset::write_docset(doc, &all_items::all_items(doc), &docset_path)?;
+    docset::write_archive(
+        doc,
+        &docset_path,
+        &version_path.join(format!("{}.tgz", doc.root.name)),
+    )?;
+    docset::write_feed(&doc.root.name, path, feed_url)?;
+
+    Ok(())
+}
+
+fn load_common_json(
+    sink: &dyn OutputSink,
+    base_path: &Path,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let Some(source) = sink.read(&base_path.join("common.js"))? else {
         return Ok(Value::Object(Default::default()));
     };
+    let source = String::from_utf8(source)?;
 
     let source = source.trim();
     let source = source
@@ -77,51 +263,126 @@ fn load_common_json(base_path: &Path) -> Result<Value, Box<dyn std::error::Error
     Ok(serde_json::de::from_str(source)?)
 }
 
-fn store_common_json(base_path: &Path, value: &Value) -> Result<(), Box<dyn std::error::Error>> {
-    let common_path = base_path.join("common.js");
+// Lists the versions in `common.js`: all of them newest first, the aliases with the version they
+// point to, and the versions grouped by channel for the version dropdown
+fn store_versions(
+    sink: &dyn OutputSink,
+    base_path: &Path,
+    versions: &[Version],
+    aliases: &[(Channel, Version)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut common = load_common_json(sink, base_path)?;
+    common["versions"] = versions
+        .iter()
+        .map(|version| Value::String(version.to_string()))
+        .collect();
+    common["aliases"] = aliases
+        .iter()
+        .map(|(channel, version)| json!([channel.alias(), version.to_string()]))
+        .collect();
+    common["channels"] = Channel::ALL
+        .into_iter()
+        .filter_map(|channel| {
+            let versions = versions
+                .iter()
+                .filter(|version| Channel::of(version) == channel)
+                .map(|version| version.to_string())
+                .collect::<Vec<_>>();
+            (!versions.is_empty()).then(|| json!([channel.title(), versions]))
+        })
+        .collect();
+    store_common_json(sink, base_path, &common)
+}
+
+fn store_common_json(
+    sink: &dyn OutputSink,
+    base_path: &Path,
+    value: &Value,
+) -> Result<(), Box<dyn std::error::Error>> {
     let source = format!(
         "window.DOCS_COMMON = {};\n",
         serde_json::ser::to_string_pretty(value)?
     );
-    fs::write(common_path, source)?;
-    Ok(())
+    sink.write(&base_path.join("common.js"), source.as_bytes())
+}
+
+// Records the version and inputs a version directory (or an alias) was generated from
+const BUILD_STAMP: &str = ".build-stamp";
+
+fn build_stamp(version: &Version, input_hash: &str) -> String {
+    format!(
+        "version {}\ninput {}\ngenerator {}\n",
+        version,
+        input_hash,
+        env!("SOURCE_HASH")
+    )
 }
 
-fn existing_versions(path: &Path) -> Result<HashSet<Version>, Box<dyn std::error::Error>> {
+// Whether `version` was already generated from the same inputs, including the aliases that point
+// to it. Such docs don't need to be compiled again.
+pub fn is_generated(
+    sink: &dyn OutputSink,
+    root_name: &str,
+    version: &Version,
+    input_hash: &str,
+) -> bool {
+    let base_path = Path::new(root_name);
+    let stamp = build_stamp(version, input_hash);
+    let matches = |path: &Path| {
+        sink.read(&path.join(BUILD_STAMP))
+            .is_ok_and(|existing| existing.is_some_and(|existing| existing == stamp.as_bytes()))
+    };
+
+    if !matches(&base_path.join(version.to_string())) {
+        return false;
+    }
+
+    let Ok(versions) = existing_versions(sink, base_path) else {
+        return false;
+    };
+    channel::aliases(&Retention::All.retain(versions))
+        .into_iter()
+        .filter(|(_, aliased)| aliased == version)
+        .all(|(channel, _)| matches(&base_path.join(channel.alias())))
+}
+
+fn existing_versions(
+    sink: &dyn OutputSink,
+    path: &Path,
+) -> Result<HashSet<Version>, Box<dyn std::error::Error>> {
     let mut versions = HashSet::new();
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_dir() {
-            if let Ok(version) = Version::parse(path.file_name().unwrap().to_str().unwrap()) {
-                versions.insert(version);
-            }
+    for name in sink.dirs(path)? {
+        if let Ok(version) = Version::parse(&name) {
+            versions.insert(version);
         }
     }
     Ok(versions)
 }
 
+// Directory of a version (or of an alias) below the directory of its crate
+fn version_dir_name(doc: &Doc, alias: Option<Channel>) -> String {
+    match alias {
+        Some(channel) => channel.alias().to_string(),
+        None => doc.version.to_string(),
+    }
+}
+
+// Writes the pages of a version into `version_path`, which is emptied first
 fn gen_doc(
+    sink: &dyn OutputSink,
     doc: &Doc,
-    build_as_latest: bool,
-    base_path: &Path,
+    alias: Option<Channel>,
+    version_path: &Path,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let base_path = if build_as_latest {
-        base_path.join("latest").join(&doc.root.name)
-    } else {
-        base_path.join(doc.version.to_string()).join(&doc.root.name)
-    };
+    let base_path = version_path.join(&doc.root.name);
 
     // Prepare directories
-    fs::remove_dir_all(&base_path).ok();
-    fs::create_dir_all(&base_path)?;
+    sink.remove_dir_all(version_path)?;
 
     // Gen modules
     gen_module(
-        &Base {
-            doc,
-            build_as_latest,
-        },
+        sink,
+        &Base { doc, alias },
         &ModulePath {
             segments: vec![(
                 doc.root.name.clone(),
@@ -134,38 +395,86 @@ fn gen_doc(
         &base_path,
     )?;
 
-    // Store items
+    // Gen source pages
+    for (path, source) in &doc.sources {
+        let template = SourceTemplate { doc, path, source };
+        sink.write(
+            &version_path.join(format!("{}.html", path)),
+            template.render()?.as_bytes(),
+        )?;
+    }
+
+    // Store search index
     let items = all_items::all_items(doc);
-    let source = format!(
-        "window.DOCS_ITEMS = {};\n",
-        serde_json::ser::to_string(&items)?
-    );
-    fs::write(base_path.join("items.js"), source)?;
+    search_index::write_search_index(sink, &items, &base_path)?;
+
+    // Store build stamp
+    if let Some(input_hash) = &doc.input_hash {
+        sink.write(
+            &version_path.join(BUILD_STAMP),
+            build_stamp(&doc.version, input_hash).as_bytes(),
+        )?;
+    }
 
     Ok(())
 }
 
 fn gen_module(
+    sink: &dyn OutputSink,
     base: &Base,
     module_path: &ModulePath,
     module: &Module,
     base_path: &Path,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let path = module_path
+        .segments
+        .iter()
+        .skip(1)
+        .map(|(name, _, _)| name.clone())
+        .collect::<Vec<_>>();
+    let bindings = module_bindings(base.doc, &path, module);
+
     let template = OverviewTemplate {
         base,
         title: &module.name,
         module_path,
         module,
+        bindings: &bindings,
     };
-    template.write_into(&mut File::create(base_path.join("index.html"))?)?;
+    sink.write(&base_path.join("index.html"), template.render()?.as_bytes())?;
+
+    if module.source_url.is_some() {
+        sink.write(
+            &base_path.join(format!("{}.wgsl", module.name)),
+            wgsl::module_to_wgsl(base.doc, &path, module).as_bytes(),
+        )?;
+    }
+
+    if !bindings.is_empty() {
+        let module_path = module_path.extend("Bindings", "#", ItemKind::Bindings, false);
+        let template = BindingsTemplate {
+            base,
+            title: &format!("{} bindings", module.name),
+            module_path: &module_path,
+            module,
+            bindings: &bindings,
+        };
+        sink.write(
+            &base_path.join("bindings.html"),
+            template.render()?.as_bytes(),
+        )?;
+    }
 
     for module in &module.modules {
         let module_path = module_path.extend(&module.name, "index.html", ItemKind::Module, true);
 
-        let base_path = base_path.join(&module.name);
-        fs::create_dir(&base_path)?;
-
-        gen_module(base, &module_path, module, &base_path)?;
+        gen_module(
+            sink,
+            base,
+            &module_path,
+            module,
+            &base_path.join(&module.name),
+        )?;
     }
 
     for constant in &module.constants {
@@ -178,9 +487,26 @@ fn gen_module(
             module,
             constant,
         };
-        template.write_into(&mut File::create(
-            base_path.join(format!("const.{}.html", constant.name)),
-        )?)?;
+        sink.write(
+            &base_path.join(format!("const.{}.html", constant.name)),
+            template.render()?.as_bytes(),
+        )?;
+    }
+
+    for override_ in &module.overrides {
+        let module_path =
+            module_path.extend(override_.name.to_string(), "#", ItemKind::Override, false);
+        let template = OverrideTemplate {
+            base,
+            title: &override_.name.to_string(),
+            module_path: &module_path,
+            module,
+            override_,
+        };
+        sink.write(
+            &base_path.join(format!("override.{}.html", override_.name)),
+            template.render()?.as_bytes(),
+        )?;
     }
 
     for var in &module.global_variables {
@@ -193,9 +519,10 @@ fn gen_module(
             module,
             var,
         };
-        template.write_into(&mut File::create(
-            base_path.join(format!("var.{}.html", var.name)),
-        )?)?;
+        sink.write(
+            &base_path.join(format!("var.{}.html", var.name)),
+            template.render()?.as_bytes(),
+        )?;
     }
 
     for struct_ in &module.structs {
@@ -208,9 +535,10 @@ fn gen_module(
             module,
             struct_,
         };
-        template.write_into(&mut File::create(
-            base_path.join(format!("struct.{}.html", struct_.name)),
-        )?)?;
+        sink.write(
+            &base_path.join(format!("struct.{}.html", struct_.name)),
+            template.render()?.as_bytes(),
+        )?;
     }
 
     for function in &module.functions {
@@ -223,9 +551,10 @@ fn gen_module(
             module,
             function,
         };
-        template.write_into(&mut File::create(
-            base_path.join(format!("fn.{}.html", function.name)),
-        )?)?;
+        sink.write(
+            &base_path.join(format!("fn.{}.html", function.name)),
+            template.render()?.as_bytes(),
+        )?;
     }
 
     Ok(())
@@ -235,9 +564,11 @@ fn gen_module(
 enum ItemKind {
     Module,
     Constant,
+    Override,
     GlobalVariable,
     Struct,
     Function,
+    Bindings,
 }
 
 #[derive(Debug, Clone)]
@@ -272,6 +603,20 @@ impl ModulePath {
     }
 }
 
+#[derive(Template)]
+#[template(path = "landing.html")]
+struct LandingTemplate<'a> {
+    crates: &'a [(String, String, Vec<String>)],
+}
+
+#[derive(Template)]
+#[template(path = "source.html")]
+struct SourceTemplate<'a> {
+    doc: &'a Doc,
+    path: &'a str,
+    source: &'a str,
+}
+
 #[derive(Template)]
 #[template(path = "overview.html")]
 struct OverviewTemplate<'a> {
@@ -279,6 +624,7 @@ struct OverviewTemplate<'a> {
     title: &'a str,
     module_path: &'a ModulePath,
     module: &'a Module,
+    bindings: &'a [BindingRow<'a>],
 }
 
 #[derive(Template)]
@@ -291,6 +637,16 @@ struct ConstantTemplate<'a> {
     constant: &'a Constant,
 }
 
+#[derive(Template)]
+#[template(path = "override.html")]
+struct OverrideTemplate<'a> {
+    base: &'a Base<'a>,
+    title: &'a str,
+    module_path: &'a ModulePath,
+    module: &'a Module,
+    override_: &'a Override,
+}
+
 #[derive(Template)]
 #[template(path = "global_variable.html")]
 struct GlobalVariableTemplate<'a> {
@@ -322,33 +678,99 @@ struct FunctionTemplate<'a> {
 }
 
 #[derive(Template)]
-#[template(path = "render_type.html")]
-struct RenderTypeTemplate<'a> {
-    ty: &'a Type,
+#[template(path = "bindings.html")]
+struct BindingsTemplate<'a> {
+    base: &'a Base<'a>,
+    title: &'a str,
+    module_path: &'a ModulePath,
+    module: &'a Module,
+    bindings: &'a [BindingRow<'a>],
+}
+
+// Markup of types and expressions in the HTML pages, items of other modules link to their page
+struct HtmlMarkup<'a> {
     module_path_level: usize,
+    doc: &'a Doc,
 }
 
-impl RenderTypeTemplate<'_> {
-    fn render_rec(&self, ty: &Type) -> String {
-        RenderTypeTemplate {
-            ty,
-            module_path_level: self.module_path_level,
-        }
-        .to_string()
+impl Markup for HtmlMarkup<'_> {
+    fn text(&self, text: &str) -> String {
+        askama::MarkupDisplay::new_unsafe(text, askama::Html).to_string()
+    }
+
+    fn link(&self, kind: LinkKind, name: &str, def_path: &[String]) -> String {
+        let class = kind.page_prefix();
+        format!(
+            "<a class=\"{}\" href=\"{}{}.{}.html\">{}</a>",
+            class,
+            self.text(&def_path_href(def_path, &self.module_path_level, self.doc)),
+            class,
+            self.text(name),
+            self.text(name)
+        )
     }
 }
 
-fn render_type(ty: &Type, module_path_level: &usize) -> String {
-    RenderTypeTemplate {
+fn render_type(ty: &Type, module_path_level: &usize, doc: &Doc) -> String {
+    render::render_type(
         ty,
-        module_path_level: *module_path_level,
+        &HtmlMarkup {
+            module_path_level: *module_path_level,
+            doc,
+        },
+    )
+}
+
+fn render_expression(expr: &Expression, module_path_level: &usize, doc: &Doc) -> String {
+    render::render_expression(
+        expr,
+        &HtmlMarkup {
+            module_path_level: *module_path_level,
+            doc,
+        },
+    )
+}
+
+// Relative link to the directory of a module, which may belong to another documented crate
+fn def_path_href(def_path: &[String], module_path_level: &usize, doc: &Doc) -> String {
+    let mut href = "../".repeat(*module_path_level);
+    if let Some(extern_crate) = def_path
+        .first()
+        .and_then(|name| doc.extern_crates.get(name))
+    {
+        href.push_str(&format!(
+            "../../../{0}/{1}/{0}/",
+            extern_crate.name, extern_crate.version
+        ));
+    }
+    for segment in def_path {
+        href.push_str(segment);
+        href.push('/');
     }
-    .to_string()
+    href
 }
 
 struct Base<'a> {
     doc: &'a Doc,
-    build_as_latest: bool,
+    alias: Option<Channel>,
+}
+
+// Link to the lines of the item a page is about, or to the whole module. Local links are relative
+// to the directory of the version.
+fn source_href(module: &Module, module_path: &ModulePath) -> String {
+    let item_url = match module_path.segments.last() {
+        Some((_, _, ItemKind::Module | ItemKind::Bindings)) | None => None,
+        Some((name, _, _)) => module.item_source_urls.get(name),
+    };
+    let Some(url) = item_url.or(module.source_url.as_ref()) else {
+        return String::new();
+    };
+
+    if render::is_local_source(url) {
+        format!("{}../{}", "../".repeat(module_path.level), url)
+    } else {
+        url.clone()
+    }
 }
 
 fn module_path_class(kind: &ItemKind, last: &bool) -> &'static str {
@@ -359,136 +781,14 @@ fn module_path_class(kind: &ItemKind, last: &bool) -> &'static str {
     match kind {
         ItemKind::Module => "module",
         ItemKind::Constant => "const",
+        ItemKind::Override => "override",
         ItemKind::GlobalVariable => "var",
         ItemKind::Struct => "struct",
         ItemKind::Function => "fn",
+        ItemKind::Bindings => "var",
     }
 }
 
-fn display_array_size(size: &Option<u32>) -> String {
-    match size {
-        Some(size) => size.to_string(),
-        None => "?".to_string(),
-    }
-}
-
-// Copy-pasted and adapted from: naga-0.14.1
-
-fn display_binding(binding: Option<&Binding>) -> String {
-    let Some(binding) = binding else {
-        return "".to_string();
-    };
-
-    match binding {
-        Binding::BuiltIn(builtin) => {
-            let builtin = builtin_str(builtin).unwrap();
-            format!("@builtin({}) ", builtin)
-        }
-        Binding::Location {
-            location,
-            second_blend_source,
-            interpolation,
-            sampling,
-        } => {
-            let mut res = String::new();
-
-            res += &format!("@location({}) ", location);
-            if *second_blend_source {
-                res += "@second_blend_source ";
-            }
-
-            if sampling.is_some() && *sampling != Some(Sampling::Center) {
-                res += &format!(
-                    "@interpolate({}, {}) ",
-                    interpolation_str(interpolation.unwrap_or(Interpolation::Perspective)),
-                    sampling_str(sampling.unwrap_or(Sampling::Center))
-                );
-            } else if interpolation.is_some() && *interpolation != Some(Interpolation::Perspective)
-            {
-                res += &format!(
-                    "@interpolate({}) ",
-                    interpolation_str(interpolation.unwrap_or(Interpolation::Perspective))
-                );
-            }
-
-            res
-        }
-    }
-}
-
-fn builtin_str(built_in: &BuiltIn) -> Result<&'static str, Box<dyn std::error::Error>> {
-    Ok(match built_in {
-        BuiltIn::Position { .. } => "position",
-        BuiltIn::ViewIndex => "view_index",
-        BuiltIn::InstanceIndex => "instance_index",
-        BuiltIn::VertexIndex => "vertex_index",
-        BuiltIn::FragDepth => "frag_depth",
-        BuiltIn::FrontFacing => "front_facing",
-        BuiltIn::PrimitiveIndex => "primitive_index",
-        BuiltIn::SampleIndex => "sample_index",
-        BuiltIn::SampleMask => "sample_mask",
-        BuiltIn::GlobalInvocationId => "global_invocation_id",
-        BuiltIn::LocalInvocationId => "local_invocation_id",
-        BuiltIn::LocalInvocationIndex => "local_invocation_index",
-        BuiltIn::WorkGroupId => "workgroup_id",
-        BuiltIn::NumWorkGroups => "num_workgroups",
-        BuiltIn::NumSubgroups => "num_subgroups",
-        BuiltIn::SubgroupId => "subgroup_id",
-        BuiltIn::SubgroupSize => "subgroup_size",
-        BuiltIn::SubgroupInvocationId => "subgroup_invocation_id",
-        BuiltIn::DrawID => "draw_index",
-        BuiltIn::ClipDistance => "clip_distances",
-        BuiltIn::BaseInstance
-        | BuiltIn::BaseVertex
-        | BuiltIn::CullDistance
-        | BuiltIn::PointSize
-        | BuiltIn::PointCoord
-        | BuiltIn::WorkGroupSize => {
-            return Err(format!("unsupported built-in: {:?}", built_in).into())
-        }
-
-        BuiltIn::Barycentric { perspective: true } => "barycentric",
-        BuiltIn::Barycentric { perspective: false } => "barycentric_no_perspective",
-        BuiltIn::MeshTaskSize => "mesh_task_size",
-        BuiltIn::CullPrimitive => "cull_primitive",
-        BuiltIn::PointIndex => "point_index",
-        BuiltIn::LineIndices => "line_indices",
-        BuiltIn::TriangleIndices => "triangle_indices",
-        BuiltIn::VertexCount => "vertex_count",
-        BuiltIn::Vertices => "vertices",
-        BuiltIn::PrimitiveCount => "primitive_count",
-        BuiltIn::Primitives => "primitives",
-        BuiltIn::RayInvocationId => "ray_invocation_id",
-        BuiltIn::NumRayInvocations => "num_ray_invocations",
-        BuiltIn::InstanceCustomData => "instance_custom_data",
-        BuiltIn::GeometryIndex => "geometry_index",
-        BuiltIn::WorldRayOrigin => "world_ray_origin",
-        BuiltIn::WorldRayDirection => "world_ray_direction",
-        BuiltIn::ObjectRayOrigin => "object_ray_origin",
-        BuiltIn::ObjectRayDirection => "object_ray_direction",
-        BuiltIn::RayTmin => "ray_t_min",
-        BuiltIn::RayTCurrentMax => "ray_t_current_max",
-        BuiltIn::ObjectToWorld => "object_to_world",
-        BuiltIn::WorldToObject => "world_to_object",
-        BuiltIn::HitKind => "hit_kind",
-    })
-}
-
-const fn interpolation_str(interpolation: Interpolation) -> &'static str {
-    match interpolation {
-        Interpolation::Perspective => "perspective",
-        Interpolation::Linear => "linear",
-        Interpolation::Flat => "flat",
-        Interpolation::PerVertex => "per_vertex",
-    }
-}
-
-const fn sampling_str(sampling: Sampling) -> &'static str {
-    match sampling {
-        Sampling::Center => "",
-        Sampling::Centroid => "centroid",
-        Sampling::Sample => "sample",
-        Sampling::First => "first",
-        Sampling::Either => "either",
-    }
+fn rust_struct(struct_: &Struct, style: RustStyle) -> String {
+    rust::struct_to_rust(struct_, None, style)
 }
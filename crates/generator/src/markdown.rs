@@ -0,0 +1,435 @@
+use crate::render::{
+    display_address_space, display_binding, is_local_source, module_bindings, render_expression,
+    render_type, struct_layout, LayoutRow, LinkKind, Markup,
+};
+use docs::{Doc, Module};
+use std::fmt::Write;
+
+// A page per module, `{root}.md` for the root and `{root}/{def path}.md` below it
+pub struct Page {
+    pub path: String,
+    pub content: String,
+}
+
+pub fn doc_to_markdown(doc: &Doc) -> Vec<Page> {
+    let mut pages = Vec::new();
+    let mut summary = "# Summary\n\n".to_string();
+    gen_module(doc, &[], &doc.root, &mut pages, &mut summary);
+    pages.push(Page {
+        path: "SUMMARY.md".to_string(),
+        content: summary,
+    });
+    pages
+}
+
+fn page_path(doc: &Doc, def_path: &[String]) -> String {
+    if def_path.is_empty() {
+        format!("{}.md", doc.root.name)
+    } else {
+        format!("{}/{}.md", doc.root.name, def_path.join("/"))
+    }
+}
+
+fn gen_module(
+    doc: &Doc,
+    def_path: &[String],
+    module: &Module,
+    pages: &mut Vec<Page>,
+    summary: &mut String,
+) {
+    let path = page_path(doc, def_path);
+    writeln!(
+        summary,
+        "{}- [{}]({})",
+        "    ".repeat(def_path.len()),
+        escape(&module.name),
+        path
+    )
+    .unwrap();
+
+    pages.push(Page {
+        content: module_page(doc, def_path, module),
+        path,
+    });
+
+    for inner in &module.modules {
+        let def_path = def_path
+            .iter()
+            .cloned()
+            .chain([inner.name.clone()])
+            .collect::<Vec<_>>();
+        gen_module(doc, &def_path, inner, pages, summary);
+    }
+}
+
+fn module_page(doc: &Doc, def_path: &[String], module: &Module) -> String {
+    let markup = MarkdownMarkup { doc, def_path };
+    let mut out = String::new();
+
+    if def_path.is_empty() {
+        writeln!(out, "# {} {}", escape(&module.name), doc.version).unwrap();
+    } else {
+        writeln!(
+            out,
+            "# {}",
+            escape(
+                &[doc.root.name.as_str()]
+                    .into_iter()
+                    .chain(def_path.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join("::")
+            )
+        )
+        .unwrap();
+    }
+    if let Some(source_url) = module
+        .source_url
+        .as_ref()
+        .filter(|url| !is_local_source(url))
+    {
+        writeln!(out, "\n[Source]({})", source_url).unwrap();
+    }
+
+    if !module.imports.is_empty() {
+        writeln!(out, "\n## Imports\n").unwrap();
+        for import in &module.imports {
+            writeln!(out, "- `{}`", import.join("::")).unwrap();
+        }
+    }
+
+    if !module.modules.is_empty() {
+        writeln!(out, "\n## Modules\n").unwrap();
+        for inner in &module.modules {
+            let inner_path = def_path
+                .iter()
+                .cloned()
+                .chain([inner.name.clone()])
+                .collect::<Vec<_>>();
+            writeln!(
+                out,
+                "- [{}]({})",
+                escape(&inner.name),
+                markup.href(&page_path(doc, &inner_path))
+            )
+            .unwrap();
+        }
+    }
+
+    if !module.constants.is_empty() {
+        writeln!(out, "\n## Constants").unwrap();
+        for constant in &module.constants {
+            item_heading(&mut out, module, "const", &constant.name.to_string());
+            signature(
+                &mut out,
+                format!(
+                    "const {}: {} = {};",
+                    constant.name,
+                    render_type(&constant.ty, &PlainMarkup),
+                    render_expression(&constant.init, &PlainMarkup)
+                ),
+            );
+        }
+    }
+
+    if !module.overrides.is_empty() {
+        writeln!(out, "\n## Overrides").unwrap();
+        for override_ in &module.overrides {
+            item_heading(&mut out, module, "override", &override_.name.to_string());
+            let mut code = String::new();
+            if let Some(id) = override_.id {
+                write!(code, "@id({}) ", id).unwrap();
+            }
+            write!(
+                code,
+                "override {}: {}",
+                override_.name,
+                render_type(&override_.ty, &PlainMarkup)
+            )
+            .unwrap();
+            if let Some(init) = &override_.init {
+                write!(code, " = {}", render_expression(init, &PlainMarkup)).unwrap();
+            }
+            code.push(';');
+            signature(&mut out, code);
+        }
+    }
+
+    if !module.global_variables.is_empty() {
+        writeln!(out, "\n## Global Variables").unwrap();
+        for var in &module.global_variables {
+            item_heading(&mut out, module, "var", &var.name.to_string());
+            let mut code = String::new();
+            if let Some(binding) = &var.binding {
+                write!(
+                    code,
+                    "@group({}) @binding({}) ",
+                    binding.group, binding.binding
+                )
+                .unwrap();
+            }
+            write!(
+                code,
+                "var{} {}: {}",
+                var.space,
+                var.name,
+                render_type(&var.ty, &PlainMarkup)
+            )
+            .unwrap();
+            if let Some(init) = &var.init {
+                write!(code, " = {}", render_expression(init, &PlainMarkup)).unwrap();
+            }
+            code.push(';');
+            signature(&mut out, code);
+        }
+    }
+
+    let bindings = module_bindings(doc, def_path, module);
+    if !bindings.is_empty() {
+        writeln!(out, "\n## Bindings\n").unwrap();
+        writeln!(
+            out,
+            "| Group | Binding | Name | Address Space | Type | Module |"
+        )
+        .unwrap();
+        writeln!(out, "| --- | --- | --- | --- | --- | --- |").unwrap();
+        for row in &bindings {
+            let name = row.var.name.to_string();
+            writeln!(
+                out,
+                "| {} | {} | {}{} | {} | {} | `{}` |",
+                row.group,
+                row.binding,
+                markup.item_link("var", &name, &row.def_path),
+                if row.collision { " (collision)" } else { "" },
+                escape(&display_address_space(&row.var.space)),
+                render_type(&row.var.ty, &markup),
+                row.def_path.join("::")
+            )
+            .unwrap();
+        }
+    }
+
+    if !module.structs.is_empty() {
+        writeln!(out, "\n## Structs").unwrap();
+        for struct_ in &module.structs {
+            item_heading(&mut out, module, "struct", &struct_.name.to_string());
+            let mut code = format!("struct {} {{", struct_.name);
+            for (i, member) in struct_.members.iter().enumerate() {
+                write!(
+                    code,
+                    "\n    {}{}: {}",
+                    display_binding(member.binding.as_ref()),
+                    member.name,
+                    render_type(&member.ty, &PlainMarkup)
+                )
+                .unwrap();
+                if i + 1 < struct_.members.len() {
+                    code.push(',');
+                }
+            }
+            code.push_str("\n}");
+            signature(&mut out, code);
+
+            writeln!(
+                out,
+                "\nSize: {} bytes, alignment: {} bytes\n",
+                struct_.size, struct_.align
+            )
+            .unwrap();
+            writeln!(out, "| Offset | Size | Align | Member | Type |").unwrap();
+            writeln!(out, "| --- | --- | --- | --- | --- |").unwrap();
+            for row in struct_layout(struct_) {
+                match row {
+                    LayoutRow::Member {
+                        member,
+                        runtime_sized,
+                    } => {
+                        let mut ty = render_type(&member.ty, &markup);
+                        if let Some(stride) = member.stride {
+                            write!(ty, " (stride {})", stride).unwrap();
+                        }
+                        writeln!(
+                            out,
+                            "| {} | {} | {} | {} | {} |",
+                            member.offset,
+                            if runtime_sized {
+                                "runtime".to_string()
+                            } else {
+                                member.size.to_string()
+                            },
+                            member.align,
+                            escape(&member.name.to_string()),
+                            ty
+                        )
+                        .unwrap();
+                    }
+                    LayoutRow::Padding { offset, size } => {
+                        writeln!(out, "| {} | {} | | padding | |", offset, size).unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    if !module.functions.is_empty() {
+        writeln!(out, "\n## Functions").unwrap();
+        for function in &module.functions {
+            item_heading(&mut out, module, "fn", &function.name.to_string());
+            let arguments = function
+                .arguments
+                .iter()
+                .map(|arg| {
+                    format!(
+                        "{}{}: {}",
+                        display_binding(arg.binding.as_ref()),
+                        arg.name,
+                        render_type(&arg.ty, &PlainMarkup)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let mut code = if arguments.len() > 3 {
+                format!(
+                    "fn {}(\n    {}\n)",
+                    function.name,
+                    arguments.join(",\n    ")
+                )
+            } else {
+                format!("fn {}({})", function.name, arguments.join(", "))
+            };
+            if let Some(ret) = &function.ret {
+                write!(code, " -> {}", render_type(ret, &PlainMarkup)).unwrap();
+            }
+            signature(&mut out, code);
+
+            if !function.arguments.is_empty() {
+                writeln!(out, "\n| Argument | Type |").unwrap();
+                writeln!(out, "| --- | --- |").unwrap();
+                for arg in &function.arguments {
+                    writeln!(
+                        out,
+                        "| {}{} | {} |",
+                        escape(&display_binding(arg.binding.as_ref())),
+                        escape(&arg.name.to_string()),
+                        render_type(&arg.ty, &markup)
+                    )
+                    .unwrap();
+                }
+            }
+            if let Some(ret) = &function.ret {
+                writeln!(out, "\nReturns {}", render_type(ret, &markup)).unwrap();
+            }
+        }
+    }
+
+    if !module.shader_defs.is_empty() {
+        writeln!(out, "\n## Shader Definitions\n").unwrap();
+        for shader_def in &module.shader_defs {
+            writeln!(out, "- `{}`", shader_def).unwrap();
+        }
+    }
+
+    if def_path.is_empty() && !doc.compiled_with.is_empty() {
+        writeln!(out, "\n## Compiled With\n").unwrap();
+        for (key, value) in &doc.compiled_with {
+            writeln!(out, "- `{}`: `{}`", key, value).unwrap();
+        }
+    }
+
+    out
+}
+
+// Items get an explicit anchor, the ids generated from headings differ between renderers
+fn item_heading(out: &mut String, module: &Module, kind: &str, name: &str) {
+    writeln!(
+        out,
+        "\n### <a id=\"{}.{}\"></a>{}\n",
+        kind,
+        name,
+        escape(name)
+    )
+    .unwrap();
+    if let Some(source_url) = module
+        .item_source_urls
+        .get(name)
+        .filter(|url| !is_local_source(url))
+    {
+        writeln!(out, "[Source]({})\n", source_url).unwrap();
+    }
+}
+
+fn signature(out: &mut String, code: String) {
+    writeln!(out, "```wgsl\n{}\n```", code).unwrap();
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '|' => out.push_str("\\|"),
+            '*' => out.push_str("\\*"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Markup of types and expressions in prose and tables, items of the doc link to their section
+struct MarkdownMarkup<'a> {
+    doc: &'a Doc,
+    def_path: &'a [String],
+}
+
+impl MarkdownMarkup<'_> {
+    // Link to a page relative to the current one
+    fn href(&self, page_path: &str) -> String {
+        format!("{}{}", "../".repeat(self.def_path.len()), page_path)
+    }
+
+    fn item_link(&self, kind: &str, name: &str, def_path: &[String]) -> String {
+        // Items of other crates are only documented on their site
+        if def_path
+            .first()
+            .is_some_and(|name| self.doc.extern_crates.contains_key(name))
+        {
+            return escape(name);
+        }
+
+        let href = if def_path == self.def_path {
+            format!("#{}.{}", kind, name)
+        } else {
+            format!(
+                "{}#{}.{}",
+                self.href(&page_path(self.doc, def_path)),
+                kind,
+                name
+            )
+        };
+        format!("[{}]({})", escape(name), href)
+    }
+}
+
+impl Markup for MarkdownMarkup<'_> {
+    fn text(&self, text: &str) -> String {
+        escape(text)
+    }
+
+    fn link(&self, kind: LinkKind, name: &str, def_path: &[String]) -> String {
+        self.item_link(kind.page_prefix(), name, def_path)
+    }
+}
+
+// Markup of fenced code blocks, which neither escape nor link
+struct PlainMarkup;
+
+impl Markup for PlainMarkup {
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn link(&self, _kind: LinkKind, name: &str, _def_path: &[String]) -> String {
+        name.to_string()
+    }
+}
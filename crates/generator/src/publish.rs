@@ -0,0 +1,220 @@
+use crate::{
+    channel::{self, Channel},
+    sink::OutputSink,
+};
+use docs::Version;
+use std::path::{Path, PathBuf};
+
+// Versions are generated below `{root}/.staging/new` and only moved next to the published ones
+// once every version of the run succeeded. It isn't a version, so it's never listed.
+const STAGING_DIR: &str = ".staging";
+
+// Which versions stay published after a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Retention {
+    #[default]
+    All,
+    // The newest release of every `major.minor`, a release replaces its prereleases. Snapshots are
+    // retained separately and the version of every alias is always kept.
+    LatestPatch,
+}
+
+impl Retention {
+    pub fn parse(value: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match value {
+            "all" => Ok(Retention::All),
+            "latest-patch" => Ok(Retention::LatestPatch),
+            _ => Err(format!(
+                "unknown retention `{}`, expected `all` or `latest-patch`",
+                value
+            )
+            .into()),
+        }
+    }
+
+    // Retained versions of `versions`, newest first
+    pub fn retain(self, versions: impl IntoIterator<Item = Version>) -> Vec<Version> {
+        let mut versions = versions.into_iter().collect::<Vec<_>>();
+        versions.sort_by(channel::newest_first);
+
+        match self {
+            Retention::All => versions,
+            Retention::LatestPatch => {
+                // Prereleases compete with the releases of their `major.minor`, snapshots don't
+                let key = |version: &Version| {
+                    (
+                        Channel::of(version) == Channel::Snapshot,
+                        version.major,
+                        version.minor,
+                    )
+                };
+                let aliased = channel::aliases(&versions)
+                    .into_iter()
+                    .map(|(_, version)| version)
+                    .collect::<Vec<_>>();
+
+                let mut retained = Vec::<Version>::new();
+                for version in versions {
+                    if aliased.contains(&version)
+                        || !retained.iter().any(|newer| key(newer) == key(&version))
+                    {
+                        retained.push(version);
+                    }
+                }
+                retained
+            }
+        }
+    }
+}
+
+pub fn staged_path(base_path: &Path, dir_name: &str) -> PathBuf {
+    base_path.join(STAGING_DIR).join("new").join(dir_name)
+}
+
+// Finishes or rolls back what an interrupted run left behind: published directories that were
+// already moved aside are restored, staged ones are dropped
+pub fn recover(sink: &dyn OutputSink, base_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let old_path = base_path.join(STAGING_DIR).join("old");
+    let published = sink.dirs(base_path)?;
+    for dir_name in sink.dirs(&old_path)? {
+        if !published.contains(&dir_name) {
+            sink.rename(&old_path.join(&dir_name), &base_path.join(&dir_name))?;
+        }
+    }
+    sink.remove_dir_all(&base_path.join(STAGING_DIR))
+}
+
+// Swaps a staged directory with the published one. The old directory is moved aside before and
+// removed after, so readers never see a partially written version. The swap takes two renames,
+// between them the directory is missing for a moment and requests get a 404.
+pub fn publish(
+    sink: &dyn OutputSink,
+    base_path: &Path,
+    dir_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let old_path = base_path.join(STAGING_DIR).join("old").join(dir_name);
+    let target_path = base_path.join(dir_name);
+
+    sink.remove_dir_all(&old_path)?;
+    sink.rename(&target_path, &old_path)?;
+    sink.rename(&staged_path(base_path, dir_name), &target_path)?;
+    sink.remove_dir_all(&old_path)
+}
+
+// Removes the staging directory once everything was published
+pub fn clean_up(sink: &dyn OutputSink, base_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    sink.remove_dir_all(&base_path.join(STAGING_DIR))
+}
+
+// Removes the published versions that aren't retained anymore, and the aliases of channels
+// without a version
+pub fn collect_garbage(
+    sink: &dyn OutputSink,
+    base_path: &Path,
+    retained: &[Version],
+    aliases: &[(Channel, Version)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for channel in Channel::ALL {
+        if !aliases.iter().any(|(aliased, _)| *aliased == channel) {
+            sink.remove_dir_all(&base_path.join(channel.alias()))?;
+        }
+    }
+
+    for dir_name in sink.dirs(base_path)? {
+        let Ok(version) = Version::parse(&dir_name) else {
+            continue;
+        };
+        if !retained.contains(&version) {
+            sink.remove_dir_all(&base_path.join(dir_name))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retain(retention: Retention, versions: &[&str]) -> Vec<String> {
+        retention
+            .retain(
+                versions
+                    .iter()
+                    .map(|version| Version::parse(version).unwrap()),
+            )
+            .into_iter()
+            .map(|version| version.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn all_sorts_newest_first() {
+        assert_eq!(
+            retain(
+                Retention::All,
+                &[
+                    "0.18.0",
+                    "0.20.0-dev+20261001120000.bbbbbbb",
+                    "0.19.0-rc.1",
+                    "0.20.0-dev+20261018093000.aaaaaaa",
+                    "0.19.0",
+                ]
+            ),
+            [
+                "0.20.0-dev+20261018093000.aaaaaaa",
+                "0.20.0-dev+20261001120000.bbbbbbb",
+                "0.19.0",
+                "0.19.0-rc.1",
+                "0.18.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn latest_patch_keeps_the_newest_release_of_every_minor() {
+        assert_eq!(
+            retain(
+                Retention::LatestPatch,
+                &["0.17.0", "0.18.0", "0.17.3", "0.18.1", "0.18.1-rc.1"]
+            ),
+            ["0.18.1", "0.17.3"]
+        );
+        assert_eq!(
+            retain(Retention::LatestPatch, &["0.19.0-rc.2", "0.19.0-rc.1"]),
+            ["0.19.0-rc.2"]
+        );
+    }
+
+    #[test]
+    fn latest_patch_retains_every_channel() {
+        assert_eq!(
+            retain(
+                Retention::LatestPatch,
+                &[
+                    "0.20.0-dev+20261001120000.bbbbbbb",
+                    "0.20.0-rc.1",
+                    "0.20.0-dev+20261018093000.aaaaaaa",
+                    "0.20.0",
+                ]
+            ),
+            ["0.20.0", "0.20.0-dev+20261018093000.aaaaaaa"]
+        );
+        // The newest release stays next to a newer prerelease of the same `major.minor`
+        assert_eq!(
+            retain(
+                Retention::LatestPatch,
+                &["0.19.0", "0.19.1-rc.1", "0.19.1-rc.2"]
+            ),
+            ["0.19.1-rc.2", "0.19.0"]
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Retention::parse("latest-patch").unwrap(),
+            Retention::LatestPatch
+        );
+        assert!(Retention::parse("newest").is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use docs::{
+    AddressSpace, Binding, BuiltIn, Doc, Expression, GlobalVariable, Interpolation, Module,
+    Sampling, Struct, StructMember, Type,
+};
+use std::collections::HashSet;
+
+// Output format specific parts of rendering types and expressions: escaping of text, and links to
+// items that are defined in a documented module
+pub trait Markup {
+    fn text(&self, text: &str) -> String;
+
+    fn link(&self, kind: LinkKind, name: &str, def_path: &[String]) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Struct,
+    Constant,
+    Override,
+}
+
+impl LinkKind {
+    // Prefix of the item's page, also used as the CSS class of links
+    pub fn page_prefix(self) -> &'static str {
+        match self {
+            LinkKind::Struct => "struct",
+            LinkKind::Constant => "const",
+            LinkKind::Override => "override",
+        }
+    }
+}
+
+pub fn render_type(ty: &Type, markup: &impl Markup) -> String {
+    let rec = |ty: &Type| render_type(ty, markup);
+
+    match ty {
+        Type::Named {
+            name,
+            def_path: Some(def_path),
+        } => markup.link(LinkKind::Struct, name, def_path),
+        Type::Named {
+            name,
+            def_path: None,
+        } => markup.text(name),
+        Type::Pointer(inner) => {
+            format!("{}{}{}", markup.text("ptr<"), rec(inner), markup.text(">"))
+        }
+        Type::PointerWithAddressSpace {
+            base,
+            address_space,
+            maybe_access,
+        } => {
+            let mut out = format!(
+                "{}, {}",
+                markup.text(&format!("ptr<{}", address_space)),
+                rec(base)
+            );
+            if let Some(access) = maybe_access {
+                out.push_str(&markup.text(&format!(", {}", access)));
+            }
+            out.push_str(&markup.text(">"));
+            out
+        }
+        Type::ArrayConstant(inner, size) => format!(
+            "{}{}{}",
+            markup.text("array<"),
+            rec(inner),
+            markup.text(&format!(", {}>", display_array_size(size)))
+        ),
+        Type::ArrayDynamic(inner) => {
+            format!(
+                "{}{}{}",
+                markup.text("array<"),
+                rec(inner),
+                markup.text(">")
+            )
+        }
+        Type::ArrayPending {
+            base,
+            size,
+            def_path,
+        } => format!(
+            "{}{}, {}{}",
+            markup.text("array<"),
+            rec(base),
+            render_reference(LinkKind::Override, size, def_path.as_deref(), markup),
+            markup.text(">")
+        ),
+        Type::BindingArrayConstant(inner, size) => format!(
+            "{}{}{}",
+            markup.text("binding_array<"),
+            rec(inner),
+            markup.text(&format!(", {}>", display_array_size(size)))
+        ),
+        Type::BindingArrayDynamic(inner) => format!(
+            "{}{}{}",
+            markup.text("binding_array<"),
+            rec(inner),
+            markup.text(">")
+        ),
+        Type::BindingArrayPending {
+            base,
+            size,
+            def_path,
+        } => format!(
+            "{}{}, {}{}",
+            markup.text("binding_array<"),
+            rec(base),
+            render_reference(LinkKind::Override, size, def_path.as_deref(), markup),
+            markup.text(">")
+        ),
+        Type::Unnamed => markup.text("unknown"),
+    }
+}
+
+pub fn render_expression(expr: &Expression, markup: &impl Markup) -> String {
+    let rec = |expr: &Expression| render_expression(expr, markup);
+    let operand = |expr: &Expression| {
+        if expr.is_compound() {
+            format!("({})", rec(expr))
+        } else {
+            rec(expr)
+        }
+    };
+
+    match expr {
+        Expression::Literal(literal) => markup.text(&literal.to_string()),
+        Expression::Constant { name, def_path } => {
+            render_reference(LinkKind::Constant, name, def_path.as_deref(), markup)
+        }
+        Expression::Override { name, def_path } => {
+            render_reference(LinkKind::Override, name, def_path.as_deref(), markup)
+        }
+        Expression::ZeroValue(ty) => format!("{}()", render_type(ty, markup)),
+        Expression::Compose { ty, components } => format!(
+            "{}({})",
+            render_type(ty, markup),
+            components.iter().map(rec).collect::<Vec<_>>().join(", ")
+        ),
+        Expression::Splat { size, value } => format!("vec{}({})", size, rec(value)),
+        Expression::Unary { op, expr } if expr.is_compound_unary_operand() => {
+            format!("{}({})", markup.text(&op.to_string()), rec(expr))
+        }
+        Expression::Unary { op, expr } => format!("{}{}", markup.text(&op.to_string()), rec(expr)),
+        Expression::Binary { op, left, right } => format!(
+            "{} {} {}",
+            operand(left),
+            markup.text(&op.to_string()),
+            operand(right)
+        ),
+        Expression::As {
+            expr,
+            scalar,
+            convert,
+        } => {
+            if *convert {
+                format!("{}({})", markup.text(&scalar.to_string()), rec(expr))
+            } else {
+                format!(
+                    "{}({})",
+                    markup.text(&format!("bitcast<{}>", scalar)),
+                    rec(expr)
+                )
+            }
+        }
+        Expression::Unknown => markup.text(".."),
+    }
+}
+
+fn render_reference(
+    kind: LinkKind,
+    name: &str,
+    def_path: Option<&[String]>,
+    markup: &impl Markup,
+) -> String {
+    match def_path {
+        Some(def_path) => markup.link(kind, name, def_path),
+        None => markup.text(name),
+    }
+}
+
+pub fn display_address_space(space: &AddressSpace) -> String {
+    match space {
+        AddressSpace::Handle => "handle".to_string(),
+        space => space
+            .to_string()
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .to_string(),
+    }
+}
+
+pub struct BindingRow<'a> {
+    pub group: u32,
+    pub binding: u32,
+    pub def_path: Vec<String>,
+    pub var: &'a GlobalVariable,
+    pub collision: bool,
+}
+
+// Local source links are relative to the directory of the version, only the HTML pages have them
+pub fn is_local_source(url: &str) -> bool {
+    !url.contains("://")
+}
+
+// Collects the resources of a module and of everything it (transitively) imports
+pub fn module_bindings<'a>(
+    doc: &'a Doc,
+    path: &[String],
+    module: &'a Module,
+) -> Vec<BindingRow<'a>> {
+    fn collect<'a>(
+        doc: &'a Doc,
+        path: &[String],
+        module: &'a Module,
+        visited: &mut HashSet<Vec<String>>,
+        rows: &mut Vec<BindingRow<'a>>,
+    ) {
+        if !visited.insert(path.to_vec()) {
+            return;
+        }
+
+        for var in &module.global_variables {
+            if let Some(binding) = &var.binding {
+                rows.push(BindingRow {
+                    group: binding.group,
+                    binding: binding.binding,
+                    def_path: path.to_vec(),
+                    var,
+                    collision: false,
+                });
+            }
+        }
+
+        for import in &module.imports {
+            if let Some(imported) = find_module(&doc.root, import) {
+                collect(doc, import, imported, visited, rows);
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    collect(doc, path, module, &mut HashSet::new(), &mut rows);
+    rows.sort_by(|a, b| {
+        (a.group, a.binding, &a.def_path, a.var.name.to_string()).cmp(&(
+            b.group,
+            b.binding,
+            &b.def_path,
+            b.var.name.to_string(),
+        ))
+    });
+
+    for idx in 0..rows.len() {
+        rows[idx].collision = rows.iter().enumerate().any(|(other, row)| {
+            other != idx && row.group == rows[idx].group && row.binding == rows[idx].binding
+        });
+    }
+
+    rows
+}
+
+pub fn find_module<'a>(root: &'a Module, path: &[String]) -> Option<&'a Module> {
+    let mut module = root;
+    for segment in path {
+        module = module
+            .modules
+            .iter()
+            .find(|module| &module.name == segment)?;
+    }
+    Some(module)
+}
+
+pub enum LayoutRow<'a> {
+    Member {
+        member: &'a StructMember,
+        runtime_sized: bool,
+    },
+    Padding {
+        offset: u32,
+        size: u32,
+    },
+}
+
+pub fn struct_layout(struct_: &Struct) -> Vec<LayoutRow<'_>> {
+    let mut rows = Vec::new();
+    let mut end = 0;
+    for member in &struct_.members {
+        if member.offset > end {
+            rows.push(LayoutRow::Padding {
+                offset: end,
+                size: member.offset - end,
+            });
+        }
+
+        let runtime_sized = matches!(member.ty, Type::ArrayDynamic(_));
+        rows.push(LayoutRow::Member {
+            member,
+            runtime_sized,
+        });
+
+        end = member.offset + member.size;
+        if runtime_sized {
+            return rows;
+        }
+    }
+
+    if struct_.size > end {
+        rows.push(LayoutRow::Padding {
+            offset: end,
+            size: struct_.size - end,
+        });
+    }
+
+    rows
+}
+
+fn display_array_size(size: &Option<u32>) -> String {
+    match size {
+        Some(size) => size.to_string(),
+        None => "?".to_string(),
+    }
+}
+
+// Copy-pasted and adapted from: naga-0.14.1
+
+pub fn display_binding(binding: Option<&Binding>) -> String {
+    let Some(binding) = binding else {
+        return "".to_string();
+    };
+
+    match binding {
+        Binding::BuiltIn(builtin) => {
+            let builtin = builtin_str(builtin).unwrap();
+            format!("@builtin({}) ", builtin)
+        }
+        Binding::Location {
+            location,
+            second_blend_source,
+            interpolation,
+            sampling,
+        } => {
+            let mut res = String::new();
+
+            res += &format!("@location({}) ", location);
+            if *second_blend_source {
+                res += "@second_blend_source ";
+            }
+
+            if sampling.is_some() && *sampling != Some(Sampling::Center) {
+                res += &format!(
+                    "@interpolate({}, {}) ",
+                    interpolation_str(interpolation.unwrap_or(Interpolation::Perspective)),
+                    sampling_str(sampling.unwrap_or(Sampling::Center))
+                );
+            } else if interpolation.is_some() && *interpolation != Some(Interpolation::Perspective)
+            {
+                res += &format!(
+                    "@interpolate({}) ",
+                    interpolation_str(interpolation.unwrap_or(Interpolation::Perspective))
+                );
+            }
+
+            res
+        }
+    }
+}
+
+fn builtin_str(built_in: &BuiltIn) -> Result<&'static str, Box<dyn std::error::Error>> {
+    Ok(match built_in {
+        BuiltIn::Position { .. } => "position",
+        BuiltIn::ViewIndex => "view_index",
+        BuiltIn::InstanceIndex => "instance_index",
+        BuiltIn::VertexIndex => "vertex_index",
+        BuiltIn::FragDepth => "frag_depth",
+        BuiltIn::FrontFacing => "front_facing",
+        BuiltIn::PrimitiveIndex => "primitive_index",
+        BuiltIn::SampleIndex => "sample_index",
+        BuiltIn::SampleMask => "sample_mask",
+        BuiltIn::GlobalInvocationId => "global_invocation_id",
+        BuiltIn::LocalInvocationId => "local_invocation_id",
+        BuiltIn::LocalInvocationIndex => "local_invocation_index",
+        BuiltIn::WorkGroupId => "workgroup_id",
+        BuiltIn::NumWorkGroups => "num_workgroups",
+        BuiltIn::NumSubgroups => "num_subgroups",
+        BuiltIn::SubgroupId => "subgroup_id",
+        BuiltIn::SubgroupSize => "subgroup_size",
+        BuiltIn::SubgroupInvocationId => "subgroup_invocation_id",
+        BuiltIn::DrawID => "draw_index",
+        BuiltIn::ClipDistance => "clip_distances",
+        BuiltIn::BaseInstance
+        | BuiltIn::BaseVertex
+        | BuiltIn::CullDistance
+        | BuiltIn::PointSize
+        | BuiltIn::PointCoord
+        | BuiltIn::WorkGroupSize => {
+            return Err(format!("unsupported built-in: {:?}", built_in).into())
+        }
+
+        BuiltIn::Barycentric { perspective: true } => "barycentric",
+        BuiltIn::Barycentric { perspective: false } => "barycentric_no_perspective",
+        BuiltIn::MeshTaskSize => "mesh_task_size",
+        BuiltIn::CullPrimitive => "cull_primitive",
+        BuiltIn::PointIndex => "point_index",
+        BuiltIn::LineIndices => "line_indices",
+        BuiltIn::TriangleIndices => "triangle_indices",
+        BuiltIn::VertexCount => "vertex_count",
+        BuiltIn::Vertices => "vertices",
+        BuiltIn::PrimitiveCount => "primitive_count",
+        BuiltIn::Primitives => "primitives",
+        BuiltIn::RayInvocationId => "ray_invocation_id",
+        BuiltIn::NumRayInvocations => "num_ray_invocations",
+        BuiltIn::InstanceCustomData => "instance_custom_data",
+        BuiltIn::GeometryIndex => "geometry_index",
+        BuiltIn::WorldRayOrigin => "world_ray_origin",
+        BuiltIn::WorldRayDirection => "world_ray_direction",
+        BuiltIn::ObjectRayOrigin => "object_ray_origin",
+        BuiltIn::ObjectRayDirection => "object_ray_direction",
+        BuiltIn::RayTmin => "ray_t_min",
+        BuiltIn::RayTCurrentMax => "ray_t_current_max",
+        BuiltIn::ObjectToWorld => "object_to_world",
+        BuiltIn::WorldToObject => "world_to_object",
+        BuiltIn::HitKind => "hit_kind",
+    })
+}
+
+const fn interpolation_str(interpolation: Interpolation) -> &'static str {
+    match interpolation {
+        Interpolation::Perspective => "perspective",
+        Interpolation::Linear => "linear",
+        Interpolation::Flat => "flat",
+        Interpolation::PerVertex => "per_vertex",
+    }
+}
+
+const fn sampling_str(sampling: Sampling) -> &'static str {
+    match sampling {
+        Sampling::Center => "",
+        Sampling::Centroid => "centroid",
+        Sampling::Sample => "sample",
+        Sampling::First => "first",
+        Sampling::Either => "either",
+    }
+}
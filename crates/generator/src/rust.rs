@@ -0,0 +1,463 @@
+use crate::{struct_layout, LayoutRow};
+use docs::{Constant, Doc, Expression, Ident, Literal, Module, Struct, Type};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RustStyle {
+    Encase,
+    Bytemuck,
+}
+
+impl RustStyle {
+    pub fn parse(value: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match value {
+            "encase" => Ok(RustStyle::Encase),
+            "bytemuck" => Ok(RustStyle::Bytemuck),
+            _ => Err(format!(
+                "unknown rust style `{}`, expected `encase` or `bytemuck`",
+                value
+            )
+            .into()),
+        }
+    }
+}
+
+pub fn doc_to_rust(doc: &Doc, style: RustStyle) -> String {
+    let mut out = format!(
+        "// Generated from the shaders of {} {}\n",
+        doc.root.name, doc.version
+    );
+    write_module(&mut out, &doc.root, &[], style);
+    out
+}
+
+fn write_module(out: &mut String, module: &Module, path: &[String], style: RustStyle) {
+    let indent = "    ".repeat(path.len());
+
+    for constant in &module.constants {
+        out.push('\n');
+        for line in constant_to_rust(constant, style).lines() {
+            writeln!(out, "{}{}", indent, line).unwrap();
+        }
+    }
+
+    for struct_ in &module.structs {
+        out.push('\n');
+        for line in struct_to_rust(struct_, Some(path), style).lines() {
+            writeln!(out, "{}{}", indent, line).unwrap();
+        }
+    }
+
+    for inner in &module.modules {
+        let path = path
+            .iter()
+            .cloned()
+            .chain([inner.name.clone()])
+            .collect::<Vec<_>>();
+
+        writeln!(out, "\n{}pub mod {} {{", indent, ident(&inner.name)).unwrap();
+        write_module(out, inner, &path, style);
+        writeln!(out, "{}}}", indent).unwrap();
+    }
+}
+
+// With `module_path` set, struct references are written relative to that module (as in the
+// generated file), otherwise they are written as absolute paths.
+pub fn struct_to_rust(
+    struct_: &Struct,
+    module_path: Option<&[String]>,
+    style: RustStyle,
+) -> String {
+    let mut out = String::new();
+    let mut pad = 0;
+
+    match style {
+        RustStyle::Encase => {
+            writeln!(out, "#[derive(Clone, Debug, encase::ShaderType)]").unwrap();
+        }
+        RustStyle::Bytemuck => {
+            writeln!(out, "#[repr(C)]").unwrap();
+            writeln!(
+                out,
+                "#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]"
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "pub struct {} {{", struct_.name).unwrap();
+
+    for row in struct_layout(struct_) {
+        match row {
+            LayoutRow::Member {
+                member,
+                runtime_sized,
+            } => {
+                let name = match &member.name {
+                    Ident::Named(name) => ident(name),
+                    Ident::Unnamed => "unknown".to_string(),
+                };
+
+                match (style, runtime_sized) {
+                    (RustStyle::Encase, true) => {
+                        let Type::ArrayDynamic(inner) = &member.ty else {
+                            unreachable!()
+                        };
+                        writeln!(out, "    #[size(runtime)]").unwrap();
+                        writeln!(
+                            out,
+                            "    pub {}: Vec<{}>,",
+                            name,
+                            rust_type(inner, module_path, style, true)
+                                .unwrap_or_else(|| fallback_type(member.size))
+                        )
+                        .unwrap();
+                    }
+                    (RustStyle::Bytemuck, true) => {
+                        writeln!(
+                            out,
+                            "    // `{}` is runtime-sized and has been omitted",
+                            name
+                        )
+                        .unwrap();
+                    }
+                    (_, false) => match rust_type(&member.ty, module_path, style, false) {
+                        Some(ty) => writeln!(out, "    pub {}: {},", name, ty).unwrap(),
+                        None => {
+                            writeln!(out, "    // unsupported type `{}`", member.ty).unwrap();
+                            writeln!(out, "    pub {}: {},", name, fallback_type(member.size))
+                                .unwrap();
+                        }
+                    },
+                }
+            }
+            LayoutRow::Padding { size, .. } => {
+                // encase inserts padding on its own
+                if style == RustStyle::Bytemuck {
+                    writeln!(out, "    pub _pad{}: [u8; {}],", pad, size).unwrap();
+                    pad += 1;
+                }
+            }
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn constant_to_rust(constant: &Constant, style: RustStyle) -> String {
+    let value = rust_value(&constant.init, style);
+    let ty = rust_type(&constant.ty, None, style, false).or_else(|| match &constant.init {
+        Expression::Literal(Literal::AbstractInt(_)) => Some("i64".to_string()),
+        Expression::Literal(Literal::AbstractFloat(_)) => Some("f64".to_string()),
+        _ => None,
+    });
+
+    match (ty, value) {
+        (Some(ty), Some(value)) => format!("pub const {}: {} = {};", constant.name, ty, value),
+        _ => format!(
+            "// const {}: {} = {}; (not representable)",
+            constant.name, constant.ty, constant.init
+        ),
+    }
+}
+
+fn rust_value(expr: &Expression, style: RustStyle) -> Option<String> {
+    match expr {
+        Expression::Literal(literal) => Some(rust_literal(literal)),
+        Expression::Compose { ty, components } => {
+            let (_, scalar, size) = parse_vector(type_name(ty)?)?;
+            let components = components
+                .iter()
+                .map(|component| match component {
+                    Expression::Literal(literal) => Some(rust_literal(literal)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            if components.len() != size {
+                return None;
+            }
+
+            Some(match style {
+                RustStyle::Encase => format!(
+                    "{}::new({})",
+                    glam_vector(scalar, size)?,
+                    components.join(", ")
+                ),
+                RustStyle::Bytemuck => format!("[{}]", components.join(", ")),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn rust_literal(literal: &Literal) -> String {
+    // Debug formatting keeps the decimal point (and the shortest round-tripping digits)
+    fn float<T: Into<f64> + std::fmt::Debug + Copy>(value: T, ty: &str) -> String {
+        let wide = value.into();
+        if wide.is_nan() {
+            format!("{}::NAN", ty)
+        } else if wide.is_infinite() && wide > 0.0 {
+            format!("{}::INFINITY", ty)
+        } else if wide.is_infinite() {
+            format!("{}::NEG_INFINITY", ty)
+        } else {
+            format!("{:?}", value)
+        }
+    }
+
+    match *literal {
+        Literal::F64(value) | Literal::AbstractFloat(value) => float(value, "f64"),
+        Literal::F32(value) | Literal::F16(value) => float(value, "f32"),
+        Literal::U32(value) => value.to_string(),
+        Literal::I32(value) => value.to_string(),
+        Literal::U64(value) => value.to_string(),
+        Literal::I64(value) | Literal::AbstractInt(value) => value.to_string(),
+        // bool is passed as u32, see `rust_scalar`
+        Literal::Bool(value) => u32::from(value).to_string(),
+    }
+}
+
+// Array elements are padded to their WGSL stride for bytemuck, which only matters for `vec3`.
+fn rust_type(
+    ty: &Type,
+    module_path: Option<&[String]>,
+    style: RustStyle,
+    array_element: bool,
+) -> Option<String> {
+    match ty {
+        Type::Named {
+            name,
+            def_path: Some(def_path),
+        } if !name.contains('<') => Some(struct_path(name, def_path, module_path)),
+        Type::Named { name, .. } => rust_builtin_type(name, style, array_element),
+        Type::ArrayConstant(inner, Some(size)) => Some(format!(
+            "[{}; {}]",
+            rust_type(inner, module_path, style, true)?,
+            size
+        )),
+        _ => None,
+    }
+}
+
+fn rust_builtin_type(name: &str, style: RustStyle, array_element: bool) -> Option<String> {
+    if let Some(scalar) = rust_scalar(name) {
+        return Some(scalar.to_string());
+    }
+
+    if let Some(inner) = name
+        .strip_prefix("atomic<")
+        .and_then(|name| name.strip_suffix('>'))
+    {
+        return rust_scalar(inner).map(str::to_string);
+    }
+
+    if let Some((_, scalar, size)) = parse_vector(name) {
+        return match style {
+            RustStyle::Encase => glam_vector(scalar, size).map(str::to_string),
+            RustStyle::Bytemuck => {
+                let size = if array_element && size == 3 { 4 } else { size };
+                Some(format!("[{}; {}]", rust_scalar(scalar)?, size))
+            }
+        };
+    }
+
+    let (columns, rows, scalar) = parse_matrix(name)?;
+    match style {
+        RustStyle::Encase if columns == rows && scalar == "f32" => match columns {
+            2 => Some("glam::Mat2".to_string()),
+            3 => Some("glam::Mat3".to_string()),
+            4 => Some("glam::Mat4".to_string()),
+            _ => None,
+        },
+        RustStyle::Encase => None,
+        RustStyle::Bytemuck => {
+            let rows = if rows == 3 { 4 } else { rows };
+            Some(format!(
+                "[[{}; {}]; {}]",
+                rust_scalar(scalar)?,
+                rows,
+                columns
+            ))
+        }
+    }
+}
+
+fn rust_scalar(name: &str) -> Option<&'static str> {
+    match name {
+        "f32" => Some("f32"),
+        "f64" => Some("f64"),
+        "i32" => Some("i32"),
+        "u32" => Some("u32"),
+        "i64" => Some("i64"),
+        "u64" => Some("u64"),
+        // bool is not host-shareable, it is passed as u32
+        "bool" => Some("u32"),
+        _ => None,
+    }
+}
+
+fn glam_vector(scalar: &str, size: usize) -> Option<&'static str> {
+    Some(match (scalar, size) {
+        ("f32", 2) => "glam::Vec2",
+        ("f32", 3) => "glam::Vec3",
+        ("f32", 4) => "glam::Vec4",
+        ("f64", 2) => "glam::DVec2",
+        ("f64", 3) => "glam::DVec3",
+        ("f64", 4) => "glam::DVec4",
+        ("i32", 2) => "glam::IVec2",
+        ("i32", 3) => "glam::IVec3",
+        ("i32", 4) => "glam::IVec4",
+        ("u32", 2) => "glam::UVec2",
+        ("u32", 3) => "glam::UVec3",
+        ("u32", 4) => "glam::UVec4",
+        _ => return None,
+    })
+}
+
+fn type_name(ty: &Type) -> Option<&str> {
+    match ty {
+        Type::Named { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+// Parses `vec3<f32>` into ("vec3", "f32", 3)
+fn parse_vector(name: &str) -> Option<(&str, &str, usize)> {
+    let (head, scalar) = name.strip_suffix('>')?.split_once('<')?;
+    let size = head.strip_prefix("vec")?.parse().ok()?;
+    Some((head, scalar, size))
+}
+
+// Parses `mat4x3<f32>` into (4, 3, "f32")
+fn parse_matrix(name: &str) -> Option<(usize, usize, &str)> {
+    let (head, scalar) = name.strip_suffix('>')?.split_once('<')?;
+    let (columns, rows) = head.strip_prefix("mat")?.split_once('x')?;
+    Some((columns.parse().ok()?, rows.parse().ok()?, scalar))
+}
+
+fn struct_path(name: &str, def_path: &[String], module_path: Option<&[String]>) -> String {
+    match module_path {
+        Some(module_path) if module_path == def_path => name.to_string(),
+        Some(module_path) => {
+            let mut path = "super::".repeat(module_path.len());
+            for segment in def_path {
+                path.push_str(&ident(segment));
+                path.push_str("::");
+            }
+            path + name
+        }
+        None => def_path
+            .iter()
+            .map(|segment| ident(segment))
+            .chain([name.to_string()])
+            .collect::<Vec<_>>()
+            .join("::"),
+    }
+}
+
+fn fallback_type(size: u32) -> String {
+    format!("[u32; {}]", size / 4)
+}
+
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else",
+        "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+        "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while", "yield",
+    ];
+
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use docs::StructMember;
+
+    fn named(name: &str) -> Type {
+        Type::Named {
+            name: name.to_string(),
+            def_path: None,
+        }
+    }
+
+    fn member(name: &str, ty: Type, offset: u32, size: u32) -> StructMember {
+        StructMember {
+            name: Ident::Named(name.to_string()),
+            ty,
+            binding: None,
+            offset,
+            size,
+            align: 16,
+            stride: None,
+        }
+    }
+
+    #[test]
+    fn bytemuck_pads_vec3_and_mat3x3() {
+        let struct_ = Struct {
+            name: Ident::Named("Mesh".to_string()),
+            members: vec![
+                member("position", named("vec3<f32>"), 0, 12),
+                member("scale", named("f32"), 12, 4),
+                member(
+                    "corners",
+                    Type::ArrayConstant(Box::new(named("vec3<f32>")), Some(2)),
+                    16,
+                    32,
+                ),
+                member("normal", named("mat3x3<f32>"), 48, 48),
+                member(
+                    "normals",
+                    Type::ArrayConstant(Box::new(named("mat3x3<f32>")), Some(2)),
+                    96,
+                    96,
+                ),
+            ],
+            size: 208,
+            align: 16,
+        };
+
+        assert_eq!(
+            struct_to_rust(&struct_, None, RustStyle::Bytemuck),
+            "#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Mesh {
+    pub position: [f32; 3],
+    pub scale: f32,
+    pub corners: [[f32; 4]; 2],
+    pub normal: [[f32; 4]; 3],
+    pub normals: [[[f32; 4]; 3]; 2],
+    pub _pad0: [u8; 16],
+}
+"
+        );
+    }
+
+    #[test]
+    fn struct_paths() {
+        let def_path = ["bevy_pbr".to_string(), "type".to_string()];
+        assert_eq!(struct_path("Light", &def_path, Some(&def_path)), "Light");
+        assert_eq!(
+            struct_path(
+                "Light",
+                &def_path,
+                Some(&["bevy_render".to_string(), "view".to_string()])
+            ),
+            "super::super::bevy_pbr::r#type::Light"
+        );
+        assert_eq!(
+            struct_path("Light", &def_path, Some(&[])),
+            "bevy_pbr::r#type::Light"
+        );
+        assert_eq!(
+            struct_path("Light", &def_path, None),
+            "bevy_pbr::r#type::Light"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    all_items::{Item, ItemKind},
+    sink::OutputSink,
+};
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    path::Path,
+};
+
+// Writes `search/index.js` listing the shards and one `search/shard-{shard}.js` per crate, the
+// prefix keeps a crate named `index` from replacing the list. Every file also gets precompressed
+// `.gz` and `.br` variants.
+pub fn write_search_index(
+    sink: &dyn OutputSink,
+    items: &[Item],
+    base_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let search_path = base_path.join("search");
+
+    let mut shards = BTreeMap::<&str, Vec<&Item>>::new();
+    for item in items {
+        shards.entry(shard_name(item)).or_default().push(item);
+    }
+
+    for (name, items) in &shards {
+        let source = format!(
+            "window.DOCS_SEARCH_SHARD({}, {});\n",
+            serde_json::ser::to_string(name)?,
+            serde_json::ser::to_string(&build_shard(items))?
+        );
+        write_compressed(
+            sink,
+            &search_path.join(format!("shard-{}.js", name)),
+            &source,
+        )?;
+    }
+
+    let source = format!(
+        "window.DOCS_SEARCH_INDEX = {};\n",
+        serde_json::ser::to_string(&shards.keys().collect::<Vec<_>>())?
+    );
+    write_compressed(sink, &search_path.join("index.js"), &source)?;
+
+    Ok(())
+}
+
+// Items are sharded by crate, items of the root module go into their own shard
+fn shard_name(item: &Item) -> &str {
+    match item.path.get(1) {
+        Some(name) => name,
+        None if matches!(item.kind, ItemKind::Module) => &item.name,
+        None => "_root",
+    }
+}
+
+// Format: `{ strings: [..], paths: [[string, ..], ..], items: [[kind, path, name, signature,
+// inputs?, output?], ..] }` where strings and paths are referenced by index and `output` is -1
+// for functions without a return type.
+fn build_shard(items: &[&Item]) -> Value {
+    let mut strings = Interner::default();
+    let mut paths = Vec::<Vec<usize>>::new();
+    let mut path_indices = HashMap::<&[String], usize>::new();
+
+    let items = items
+        .iter()
+        .map(|item| {
+            let path = *path_indices.entry(&item.path).or_insert_with(|| {
+                paths.push(item.path.iter().map(|seg| strings.intern(seg)).collect());
+                paths.len() - 1
+            });
+
+            let mut entry = vec![
+                json!(item.kind as u8),
+                json!(path),
+                json!(strings.intern(&item.name)),
+                json!(strings.intern(&item.signature)),
+            ];
+            if let Some(inputs) = &item.inputs {
+                entry.push(json!(inputs
+                    .iter()
+                    .map(|input| strings.intern(input))
+                    .collect::<Vec<_>>()));
+                entry.push(match &item.output {
+                    Some(output) => json!(strings.intern(output)),
+                    None => json!(-1),
+                });
+            }
+
+            Value::Array(entry)
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "strings": strings.strings,
+        "paths": paths,
+        "items": items,
+    })
+}
+
+#[derive(Default)]
+struct Interner {
+    strings: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl Interner {
+    fn intern(&mut self, string: &str) -> usize {
+        if let Some(&idx) = self.indices.get(string) {
+            return idx;
+        }
+
+        self.strings.push(string.to_string());
+        self.indices
+            .insert(string.to_string(), self.strings.len() - 1);
+        self.strings.len() - 1
+    }
+}
+
+fn write_compressed(
+    sink: &dyn OutputSink,
+    path: &Path,
+    source: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    sink.write(path, source.as_bytes())?;
+
+    let mut gz = GzEncoder::new(Vec::new(), Compression::best());
+    gz.write_all(source.as_bytes())?;
+    sink.write(&append_extension(path, "gz"), &gz.finish()?)?;
+
+    let mut br = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut br, 4096, 11, 22);
+        writer.write_all(source.as_bytes())?;
+    }
+    sink.write(&append_extension(path, "br"), &br)?;
+
+    Ok(())
+}
+
+fn append_extension(path: &Path, extension: &str) -> std::path::PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
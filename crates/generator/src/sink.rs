@@ -0,0 +1,262 @@
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+// Where the generated site goes. Paths are relative to the root of the sink and use `/`
+// separated components, directories are implied by the files written below them.
+pub trait OutputSink: Sync {
+    // Writes a file, creating its parent directories
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
+
+    // Contents of a file, `None` if it doesn't exist
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>>;
+
+    // Names of the directories directly below `path`, empty if `path` doesn't exist
+    fn dirs(&self, path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+
+    // Removes a file, nothing happens if it doesn't exist
+    fn remove_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>>;
+
+    // Removes a directory and everything below it, nothing happens if it doesn't exist
+    fn remove_dir_all(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>>;
+
+    // Moves a file or directory, nothing happens if `from` doesn't exist
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+// Writes below a directory on disk. Files are written next to their destination and renamed, so
+// they are never seen half-written.
+pub struct FsSink {
+    root: PathBuf,
+}
+
+impl FsSink {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FsSink { root: root.into() }
+    }
+}
+
+impl OutputSink for FsSink {
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut temp_name = OsString::from(".");
+        temp_name.push(path.file_name().ok_or("cannot write to a directory")?);
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+        fs::write(&temp_path, contents)?;
+        fs::rename(temp_path, path)?;
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        match fs::read(self.root.join(path)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn dirs(&self, path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let entries = match fs::read_dir(self.root.join(path)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut dirs = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                dirs.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        match fs::remove_file(self.root.join(path)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        match fs::remove_dir_all(self.root.join(path)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let from = self.root.join(from);
+        if !from.exists() {
+            return Ok(());
+        }
+
+        let to = self.root.join(to);
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(from, to)?;
+        Ok(())
+    }
+}
+
+// Keeps the files in memory, for tests and as the staging area of archives
+#[derive(Default)]
+pub struct MemorySink {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Files sorted by path
+    pub fn into_files(self) -> BTreeMap<PathBuf, Vec<u8>> {
+        self.files.into_inner().unwrap()
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        Ok(self.files.lock().unwrap().get(path).cloned())
+    }
+
+    fn dirs(&self, path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut dirs = Vec::new();
+        for file in self.files.lock().unwrap().keys() {
+            let Ok(rest) = file.strip_prefix(path) else {
+                continue;
+            };
+            let mut components = rest.components();
+            if let (Some(dir), Some(_)) = (components.next(), components.next()) {
+                dirs.push(dir.as_os_str().to_string_lossy().to_string());
+            }
+        }
+        dirs.dedup();
+        Ok(dirs)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.files.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|file, _| !file.starts_with(path));
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut files = self.files.lock().unwrap();
+        let moved = files
+            .keys()
+            .filter(|file| file.starts_with(from))
+            .cloned()
+            .collect::<Vec<_>>();
+        for file in moved {
+            let contents = files.remove(&file).unwrap();
+            files.insert(to.join(file.strip_prefix(from).unwrap()), contents);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    TarGz,
+    Zip,
+}
+
+// Collects the site in memory and packs it into a single archive on `finish`
+pub struct ArchiveSink {
+    files: MemorySink,
+    format: ArchiveFormat,
+}
+
+impl ArchiveSink {
+    pub fn new(format: ArchiveFormat) -> Self {
+        ArchiveSink {
+            files: MemorySink::new(),
+            format,
+        }
+    }
+
+    pub fn finish(self, archive_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(archive_path)?;
+        match self.format {
+            ArchiveFormat::TarGz => {
+                let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+                for (path, contents) in self.files.into_files() {
+                    let mut header = tar::Header::new_gnu();
+                    header.set_size(contents.len() as u64);
+                    header.set_mode(0o644);
+                    header.set_cksum();
+                    builder.append_data(&mut header, path, contents.as_slice())?;
+                }
+                builder.into_inner()?.finish()?;
+            }
+            ArchiveFormat::Zip => {
+                let mut writer = zip::ZipWriter::new(file);
+                let options = zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated);
+                for (path, contents) in self.files.into_files() {
+                    writer.start_file(path.to_string_lossy(), options)?;
+                    writer.write_all(&contents)?;
+                }
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OutputSink for ArchiveSink {
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.files.write(path, contents)
+    }
+
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        self.files.read(path)
+    }
+
+    fn dirs(&self, path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        self.files.dirs(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.files.remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.files.remove_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.files.rename(from, to)
+    }
+}
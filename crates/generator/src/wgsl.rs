@@ -0,0 +1,418 @@
+use crate::render::{display_binding, find_module};
+use docs::{Doc, Expression, Literal, Module, Type};
+use std::fmt::Write;
+
+// Calls `f` with the import path of every module that was compiled from a shader
+pub fn for_each_shader_module<'a>(
+    module: &'a Module,
+    path: &[String],
+    f: &mut impl FnMut(&[String], &'a Module),
+) {
+    if module.source_url.is_some() {
+        f(path, module);
+    }
+
+    for inner in &module.modules {
+        let path = path
+            .iter()
+            .cloned()
+            .chain([inner.name.clone()])
+            .collect::<Vec<_>>();
+        for_each_shader_module(inner, &path, f);
+    }
+}
+
+// Declarations of a module as WGSL, functions only return a zero value. Functions whose return
+// type has no zero value are left out. References to items of other modules use their full import
+// path, which naga_oil resolves without an `#import`.
+pub fn module_to_wgsl(doc: &Doc, path: &[String], module: &Module) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// Interface of `{}` from {} {}, function bodies are stubs",
+        path.join("::"),
+        doc.root.name,
+        doc.version
+    )
+    .unwrap();
+    writeln!(out, "#define_import_path {}", path.join("::")).unwrap();
+
+    if !module.imports.is_empty() {
+        out.push('\n');
+        for import in &module.imports {
+            writeln!(out, "#import {}", import.join("::")).unwrap();
+        }
+    }
+
+    if !module.constants.is_empty() {
+        out.push('\n');
+        for constant in &module.constants {
+            let ty = wgsl_type(&constant.ty, path);
+            let init = wgsl_expression(&constant.init, path).unwrap_or_else(|| format!("{}()", ty));
+            writeln!(out, "const {}: {} = {};", constant.name, ty, init).unwrap();
+        }
+    }
+
+    if !module.overrides.is_empty() {
+        out.push('\n');
+        for override_ in &module.overrides {
+            if let Some(id) = override_.id {
+                write!(out, "@id({}) ", id).unwrap();
+            }
+            write!(
+                out,
+                "override {}: {}",
+                override_.name,
+                wgsl_type(&override_.ty, path)
+            )
+            .unwrap();
+            if let Some(init) = override_
+                .init
+                .as_ref()
+                .and_then(|init| wgsl_expression(init, path))
+            {
+                write!(out, " = {}", init).unwrap();
+            }
+            writeln!(out, ";").unwrap();
+        }
+    }
+
+    for struct_ in &module.structs {
+        writeln!(out, "\nstruct {} {{", struct_.name).unwrap();
+        for member in &struct_.members {
+            writeln!(
+                out,
+                "    {}{}: {},",
+                display_binding(member.binding.as_ref()),
+                member.name,
+                wgsl_type(&member.ty, path)
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    if !module.global_variables.is_empty() {
+        out.push('\n');
+        for var in &module.global_variables {
+            if let Some(binding) = &var.binding {
+                write!(
+                    out,
+                    "@group({}) @binding({}) ",
+                    binding.group, binding.binding
+                )
+                .unwrap();
+            }
+            write!(
+                out,
+                "var{} {}: {}",
+                var.space,
+                var.name,
+                wgsl_type(&var.ty, path)
+            )
+            .unwrap();
+            if let Some(init) = var
+                .init
+                .as_ref()
+                .and_then(|init| wgsl_expression(init, path))
+            {
+                write!(out, " = {}", init).unwrap();
+            }
+            writeln!(out, ";").unwrap();
+        }
+    }
+
+    for function in &module.functions {
+        let arguments = function
+            .arguments
+            .iter()
+            .map(|arg| {
+                format!(
+                    "{}{}: {}",
+                    display_binding(arg.binding.as_ref()),
+                    arg.name,
+                    wgsl_type(&arg.ty, path)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match &function.ret {
+            Some(ret) if !constructible(doc, ret) => writeln!(
+                out,
+                "\n// fn {}({}) -> {} has no stub, its return type can't be constructed",
+                function.name,
+                arguments,
+                wgsl_type(ret, path)
+            )
+            .unwrap(),
+            Some(ret) => {
+                let ret = wgsl_type(ret, path);
+                writeln!(
+                    out,
+                    "\nfn {}({}) -> {} {{\n    return {}();\n}}",
+                    function.name, arguments, ret, ret
+                )
+                .unwrap();
+            }
+            None => writeln!(out, "\nfn {}({}) {{}}", function.name, arguments).unwrap(),
+        }
+    }
+
+    out
+}
+
+// Whether `T()` is a value of the type. Handles, atomics, pointers and arrays without a fixed
+// size aren't, nor are structs containing them.
+fn constructible(doc: &Doc, ty: &Type) -> bool {
+    match ty {
+        Type::Named {
+            name,
+            def_path: Some(def_path),
+        } => find_module(&doc.root, def_path)
+            .and_then(|module| {
+                module
+                    .structs
+                    .iter()
+                    .find(|struct_| struct_.name.to_string() == *name)
+            })
+            .is_none_or(|struct_| {
+                struct_
+                    .members
+                    .iter()
+                    .all(|member| constructible(doc, &member.ty))
+            }),
+        Type::Named { name, .. } => ![
+            "texture",
+            "sampler",
+            "atomic",
+            "acceleration_structure",
+            "ray_query",
+        ]
+        .iter()
+        .any(|prefix| name.starts_with(prefix)),
+        Type::ArrayConstant(inner, Some(_)) => constructible(doc, inner),
+        _ => false,
+    }
+}
+
+// Prefixes items defined in another module with that module's import path
+fn qualified(name: &str, def_path: &Option<Vec<String>>, module_path: &[String]) -> String {
+    match def_path {
+        Some(def_path) if def_path != module_path => format!("{}::{}", def_path.join("::"), name),
+        _ => name.to_string(),
+    }
+}
+
+fn wgsl_type(ty: &Type, module_path: &[String]) -> String {
+    fn size(size: &Option<u32>) -> String {
+        match size {
+            Some(size) => format!(", {}", size),
+            None => "".to_string(),
+        }
+    }
+
+    match ty {
+        Type::Named { name, def_path } => qualified(name, def_path, module_path),
+        Type::Pointer(inner) => format!("ptr<function, {}>", wgsl_type(inner, module_path)),
+        Type::PointerWithAddressSpace {
+            base,
+            address_space,
+            maybe_access,
+        } => match maybe_access {
+            Some(access) => format!(
+                "ptr<{}, {}, {}>",
+                address_space,
+                wgsl_type(base, module_path),
+                access
+            ),
+            None => format!("ptr<{}, {}>", address_space, wgsl_type(base, module_path)),
+        },
+        Type::ArrayConstant(inner, len) => {
+            format!("array<{}{}>", wgsl_type(inner, module_path), size(len))
+        }
+        Type::ArrayDynamic(inner) => format!("array<{}>", wgsl_type(inner, module_path)),
+        Type::ArrayPending {
+            base,
+            size,
+            def_path,
+        } => format!(
+            "array<{}, {}>",
+            wgsl_type(base, module_path),
+            qualified(size, def_path, module_path)
+        ),
+        Type::BindingArrayConstant(inner, len) => {
+            format!(
+                "binding_array<{}{}>",
+                wgsl_type(inner, module_path),
+                size(len)
+            )
+        }
+        Type::BindingArrayDynamic(inner) => {
+            format!("binding_array<{}>", wgsl_type(inner, module_path))
+        }
+        Type::BindingArrayPending {
+            base,
+            size,
+            def_path,
+        } => format!(
+            "binding_array<{}, {}>",
+            wgsl_type(base, module_path),
+            qualified(size, def_path, module_path)
+        ),
+        Type::Unnamed => ty.to_string(),
+    }
+}
+
+// Returns `None` if the expression could not be recovered by the compiler
+fn wgsl_expression(expr: &Expression, module_path: &[String]) -> Option<String> {
+    let operand = |expr: &Expression| {
+        let inner = wgsl_expression(expr, module_path)?;
+        Some(if expr.is_compound() {
+            format!("({})", inner)
+        } else {
+            inner
+        })
+    };
+
+    Some(match expr {
+        Expression::Literal(literal) => wgsl_literal(literal),
+        Expression::Constant { name, def_path } | Expression::Override { name, def_path } => {
+            qualified(name, def_path, module_path)
+        }
+        Expression::ZeroValue(ty) => format!("{}()", wgsl_type(ty, module_path)),
+        Expression::Compose { ty, components } => format!(
+            "{}({})",
+            wgsl_type(ty, module_path),
+            components
+                .iter()
+                .map(|component| wgsl_expression(component, module_path))
+                .collect::<Option<Vec<_>>>()?
+                .join(", ")
+        ),
+        Expression::Splat { size, value } => {
+            format!("vec{}({})", size, wgsl_expression(value, module_path)?)
+        }
+        Expression::Unary { op, expr } if expr.is_compound_unary_operand() => {
+            format!("{}({})", op, wgsl_expression(expr, module_path)?)
+        }
+        Expression::Unary { op, expr } => {
+            format!("{}{}", op, wgsl_expression(expr, module_path)?)
+        }
+        Expression::Binary { op, left, right } => {
+            format!("{} {} {}", operand(left)?, op, operand(right)?)
+        }
+        Expression::As {
+            expr,
+            scalar,
+            convert,
+        } => {
+            let expr = wgsl_expression(expr, module_path)?;
+            if *convert {
+                format!("{}({})", scalar, expr)
+            } else {
+                format!("bitcast<{}>({})", scalar, expr)
+            }
+        }
+        Expression::Unknown => return None,
+    })
+}
+
+// Typed literals get their suffix, so the stub doesn't depend on WGSL's abstract conversions
+fn wgsl_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::F32(value) => format!("{}f", value),
+        Literal::F16(value) => format!("{}h", value),
+        Literal::U32(value) => format!("{}u", value),
+        Literal::I32(value) => format!("{}i", value),
+        literal => literal.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use docs::{Function, IndexMap, IndexSet, UnaryOperator};
+
+    fn named(name: &str) -> Type {
+        Type::Named {
+            name: name.to_string(),
+            def_path: None,
+        }
+    }
+
+    fn negate(expr: Expression) -> Expression {
+        Expression::Unary {
+            op: UnaryOperator::Negate,
+            expr: Box::new(expr),
+        }
+    }
+
+    #[test]
+    fn nested_negations_keep_their_parentheses() {
+        let x = || Expression::Override {
+            name: "x".to_string(),
+            def_path: None,
+        };
+        assert_eq!(wgsl_expression(&negate(x()), &[]).unwrap(), "-x");
+        assert_eq!(wgsl_expression(&negate(negate(x())), &[]).unwrap(), "-(-x)");
+        assert_eq!(
+            wgsl_expression(&negate(Expression::Literal(Literal::I32(-1))), &[]).unwrap(),
+            "-(-1i)"
+        );
+    }
+
+    #[test]
+    fn functions_without_zero_value_are_left_out() {
+        let function = |name: &str, ret: Type| Function {
+            name: docs::Ident::Named(name.to_string()),
+            arguments: Vec::new(),
+            ret: Some(ret),
+        };
+        let module = Module {
+            name: "utils".to_string(),
+            source_url: Some("utils.wgsl".to_string()),
+            item_source_urls: IndexMap::new(),
+            modules: Vec::new(),
+            constants: Vec::new(),
+            overrides: Vec::new(),
+            global_variables: Vec::new(),
+            structs: Vec::new(),
+            functions: vec![
+                function("color", named("vec4<f32>")),
+                function("texture", named("texture_2d<f32>")),
+                function("lights", Type::ArrayDynamic(Box::new(named("vec4<f32>")))),
+            ],
+            shader_defs: IndexSet::new(),
+            imports: Vec::new(),
+        };
+        let doc = Doc {
+            version: docs::Version::new(0, 1, 0),
+            root: Module {
+                name: "fixtures".to_string(),
+                source_url: None,
+                item_source_urls: IndexMap::new(),
+                modules: Vec::new(),
+                constants: Vec::new(),
+                overrides: Vec::new(),
+                global_variables: Vec::new(),
+                structs: Vec::new(),
+                functions: Vec::new(),
+                shader_defs: IndexSet::new(),
+                imports: Vec::new(),
+            },
+            compiled_with: IndexMap::new(),
+            extern_crates: IndexMap::new(),
+            input_hash: None,
+            sources: IndexMap::new(),
+        };
+
+        let wgsl = module_to_wgsl(&doc, &["utils".to_string()], &module);
+        assert!(wgsl.contains("fn color() -> vec4<f32> {\n    return vec4<f32>();\n}"));
+        assert!(wgsl.contains(
+            "// fn texture() -> texture_2d<f32> has no stub, its return type can't be constructed"
+        ));
+        assert!(wgsl.contains("// fn lights() -> array<vec4<f32>> has no stub"));
+    }
+}
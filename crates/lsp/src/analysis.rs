@@ -0,0 +1,341 @@
+use crate::index::{Index, Item, ModuleEntry};
+use lsp_types::Position;
+use std::collections::HashMap;
+
+// Names the `#import` directives of a document bring into scope
+#[derive(Default)]
+pub struct Imports {
+    // Module aliases to import paths
+    pub modules: HashMap<String, String>,
+    // Item names to the import path of their module and their name there
+    pub items: HashMap<String, (String, String)>,
+}
+
+pub enum Target<'a> {
+    Module(&'a ModuleEntry),
+    Item(&'a ModuleEntry, &'a Item),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    PathSep,
+    Open,
+    Close,
+    Comma,
+}
+
+pub fn parse_imports(text: &str, index: &Index) -> Imports {
+    let mut imports = Imports::default();
+
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let Some(directive) = line.trim_start().strip_prefix("#import") else {
+            continue;
+        };
+
+        // Brace lists may span several lines
+        let mut directive = directive.to_string();
+        while directive.matches('{').count() > directive.matches('}').count() {
+            match lines.next() {
+                Some(line) => directive.push_str(line),
+                None => break,
+            }
+        }
+
+        for (path, alias) in expand_import(&tokenize(&directive)) {
+            let name = alias.unwrap_or_else(|| path.last().unwrap().clone());
+            let import_path = path.join("::");
+            if index.module(&import_path).is_some() {
+                imports.modules.insert(name, import_path);
+            } else if let Some((item, module_path)) = path.split_last() {
+                let module_path = module_path.join("::");
+                if index
+                    .module(&module_path)
+                    .is_some_and(|module| module.item(item).is_some())
+                {
+                    imports.items.insert(name, (module_path, item.clone()));
+                }
+            }
+        }
+    }
+
+    imports
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                tokens.push(Token::PathSep);
+            }
+            // Comments end the directive
+            '/' if chars.peek() == Some(&'/') => break,
+            c if is_ident_char(c) => {
+                let mut ident = c.to_string();
+                while let Some(&c) = chars.peek().filter(|c| is_ident_char(**c)) {
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+// Expands `a::{b, c::{d as e}}` into full paths with their aliases. naga_oil 0.8 imported items
+// with `a::b c, d` instead, those are expanded to `a::b::c` and `a::b::d`.
+fn expand_import(tokens: &[Token]) -> Vec<(Vec<String>, Option<String>)> {
+    let mut paths = Vec::new();
+    let mut pos = 0;
+    while pos < tokens.len() {
+        let start = paths.len();
+        expand_tree(tokens, &mut pos, &[], &mut paths);
+
+        match tokens.get(pos) {
+            Some(Token::Comma) => pos += 1,
+            Some(Token::Ident(_)) if paths.len() == start + 1 => {
+                let (module_path, _) = paths.pop().unwrap();
+                for token in &tokens[pos..] {
+                    if let Token::Ident(item) = token {
+                        let mut path = module_path.clone();
+                        path.push(item.clone());
+                        paths.push((path, None));
+                    }
+                }
+                paths.push((module_path, None));
+                break;
+            }
+            Some(_) => pos += 1,
+            None => break,
+        }
+    }
+    paths
+}
+
+fn expand_tree(
+    tokens: &[Token],
+    pos: &mut usize,
+    prefix: &[String],
+    paths: &mut Vec<(Vec<String>, Option<String>)>,
+) {
+    let mut path = prefix.to_vec();
+    loop {
+        match tokens.get(*pos) {
+            Some(Token::Ident(ident)) => {
+                path.push(ident.clone());
+                *pos += 1;
+            }
+            Some(Token::Open) => {
+                *pos += 1;
+                loop {
+                    expand_tree(tokens, pos, &path, paths);
+                    match tokens.get(*pos) {
+                        Some(Token::Comma) => *pos += 1,
+                        Some(Token::Close) => {
+                            *pos += 1;
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+                return;
+            }
+            _ => break,
+        }
+
+        if tokens.get(*pos) == Some(&Token::PathSep) {
+            *pos += 1;
+        } else {
+            break;
+        }
+    }
+
+    let mut alias = None;
+    if tokens.get(*pos) == Some(&Token::Ident("as".to_string())) {
+        if let Some(Token::Ident(name)) = tokens.get(*pos + 1) {
+            alias = Some(name.clone());
+            *pos += 2;
+        }
+    }
+
+    if path.len() > prefix.len() {
+        paths.push((path, alias));
+    }
+}
+
+// Resolves a path as written in the document, which may start with an imported name or be
+// fully qualified
+pub fn resolve<'a>(path: &[String], imports: &Imports, index: &'a Index) -> Option<Target<'a>> {
+    if let [name] = path {
+        if let Some((module_path, item)) = imports.items.get(name) {
+            let module = index.module(module_path)?;
+            return Some(Target::Item(module, module.item(item)?));
+        }
+    }
+
+    let mut candidates = vec![path.to_vec()];
+    if let Some(import_path) = imports.modules.get(&path[0]) {
+        let mut expanded = import_path
+            .split("::")
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        expanded.extend(path[1..].iter().cloned());
+        candidates.insert(0, expanded);
+    }
+
+    for path in candidates {
+        if let Some(module) = index.module(&path.join("::")) {
+            return Some(Target::Module(module));
+        }
+        if let Some((item, module_path)) = path.split_last() {
+            if let Some(module) = index.module(&module_path.join("::")) {
+                if let Some(item) = module.item(item) {
+                    return Some(Target::Item(module, item));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// Path segments up to and including the one under the cursor
+pub fn path_at(text: &str, position: Position) -> Option<Vec<String>> {
+    let line = text.lines().nth(position.line as usize)?;
+    let offset = byte_offset(line, position.character);
+
+    let start = line[..offset]
+        .rfind(|c| !is_path_char(c))
+        .map_or(0, |idx| idx + 1);
+    let end = line[offset..]
+        .find(|c| !is_path_char(c))
+        .map_or(line.len(), |idx| idx + offset);
+    let word = &line[start..end];
+
+    // Only the segments up to the cursor, so hovering a module in a path shows the module
+    let segment_end = word[offset - start..]
+        .find("::")
+        .map_or(word.len(), |idx| idx + offset - start);
+    let path = word[..segment_end]
+        .split("::")
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    if path.iter().any(|segment| segment.is_empty()) {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+// The path being typed before the cursor and whether the cursor is in an `#import` directive.
+// Inside a brace list of an import the path is prefixed with the path before the brace.
+pub fn completion_context(text: &str, position: Position) -> (String, bool) {
+    let line = text.lines().nth(position.line as usize).unwrap_or("");
+    let before = &line[..byte_offset(line, position.character)];
+    let typed_start = before.rfind(|c| !is_path_char(c)).map_or(0, |idx| idx + 1);
+    let typed = &before[typed_start..];
+
+    let Some(directive) = before.trim_start().strip_prefix("#import") else {
+        return (typed.to_string(), false);
+    };
+
+    let mut depth = 0;
+    let mut brace_prefixes = Vec::new();
+    for (idx, c) in directive.char_indices() {
+        match c {
+            '{' => {
+                let prefix = directive[..idx].trim_end().trim_end_matches("::");
+                let prefix = prefix
+                    .rfind(|c| !is_path_char(c))
+                    .map_or(prefix, |start| &prefix[start + 1..]);
+                brace_prefixes.truncate(depth);
+                brace_prefixes.push(prefix.to_string());
+                depth += 1;
+            }
+            '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    let mut path = brace_prefixes[..depth.min(brace_prefixes.len())].join("::");
+    if !path.is_empty() {
+        path.push_str("::");
+    }
+    path.push_str(typed);
+    (path, true)
+}
+
+// The path of the function whose arguments the cursor is in, and the index of the argument
+pub fn call_at(text: &str, position: Position) -> Option<(Vec<String>, u32)> {
+    let offset = text_offset(text, position)?;
+    let before = &text[..offset];
+
+    let mut depth = 0;
+    let mut active_parameter = 0;
+    let mut open = None;
+    for (idx, c) in before.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => {
+                open = Some(idx);
+                break;
+            }
+            '(' => depth -= 1,
+            ',' if depth == 0 => active_parameter += 1,
+            ';' | '{' | '}' if depth == 0 => return None,
+            _ => {}
+        }
+    }
+
+    let callee = before[..open?].trim_end();
+    let start = callee.rfind(|c| !is_path_char(c)).map_or(0, |idx| idx + 1);
+    let path = callee[start..]
+        .split("::")
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    if path.iter().any(|segment| segment.is_empty()) {
+        None
+    } else {
+        Some((path, active_parameter))
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_path_char(c: char) -> bool {
+    is_ident_char(c) || c == ':'
+}
+
+// LSP positions count UTF-16 code units
+fn byte_offset(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (idx, c) in line.char_indices() {
+        if units >= character as usize {
+            return idx;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn text_offset(text: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += text[line_start..].find('\n')? + 1;
+    }
+    let line = text[line_start..].lines().next().unwrap_or("");
+    Some(line_start + byte_offset(line, position.character))
+}
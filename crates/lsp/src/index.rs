@@ -0,0 +1,222 @@
+use docs::{Doc, Ident, IndexMap, Module};
+use regex::Regex;
+use std::{fs, path::PathBuf};
+
+// Modules of a doc by their import path, together with the files they were compiled from
+pub struct Index {
+    pub modules: IndexMap<String, ModuleEntry>,
+}
+
+pub struct ModuleEntry {
+    pub import_path: String,
+    pub source_path: Option<PathBuf>,
+    pub docs_url: Option<String>,
+    pub items: Vec<Item>,
+}
+
+pub struct Item {
+    pub name: String,
+    pub kind: ItemKind,
+    pub signature: String,
+    // Arguments of functions, shown by signature help
+    pub parameters: Vec<String>,
+    pub docs_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Constant,
+    Override,
+    GlobalVariable,
+    Struct,
+    Function,
+}
+
+impl ItemKind {
+    // Prefix of the generated page and of the declaration in the source
+    fn page_prefix(self) -> &'static str {
+        match self {
+            ItemKind::Constant => "const",
+            ItemKind::Override => "override",
+            ItemKind::GlobalVariable => "var",
+            ItemKind::Struct => "struct",
+            ItemKind::Function => "fn",
+        }
+    }
+}
+
+impl Index {
+    // `docs_url` is the root of a generated site, without it the items link to their source
+    pub fn new(
+        doc: &Doc,
+        source_paths: &IndexMap<String, PathBuf>,
+        docs_url: Option<&str>,
+    ) -> Self {
+        let mut modules = IndexMap::new();
+
+        let mut stack = vec![(Vec::new(), &doc.root)];
+        while let Some((path, module)) = stack.pop() {
+            if module.source_url.is_some() {
+                let import_path = path.join("::");
+                let docs_url = docs_url.map(|docs_url| {
+                    let page_path = [doc.root.name.clone()]
+                        .iter()
+                        .chain(&path)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    format!(
+                        "{}/{}/{}/{}",
+                        docs_url.trim_end_matches('/'),
+                        doc.root.name,
+                        doc.version,
+                        page_path
+                    )
+                });
+                let entry = ModuleEntry {
+                    source_path: source_paths.get(&import_path).cloned(),
+                    items: module_items(module, docs_url.as_deref()),
+                    docs_url: docs_url
+                        .map(|url| format!("{}/index.html", url))
+                        // Local source pages only exist next to generated docs
+                        .or_else(|| module.source_url.clone().filter(|url| url.contains("://"))),
+                    import_path: import_path.clone(),
+                };
+                modules.insert(import_path, entry);
+            }
+
+            for inner in module.modules.iter().rev() {
+                let mut path = path.clone();
+                path.push(inner.name.clone());
+                stack.push((path, inner));
+            }
+        }
+
+        modules.sort_keys();
+        Index { modules }
+    }
+
+    pub fn module(&self, import_path: &str) -> Option<&ModuleEntry> {
+        self.modules.get(import_path)
+    }
+
+    // Next segments of the import paths starting with `prefix`, or the crate names if it's empty
+    pub fn child_segments(&self, prefix: &str) -> Vec<&str> {
+        let mut segments = Vec::new();
+        for import_path in self.modules.keys() {
+            let rest = if prefix.is_empty() {
+                Some(import_path.as_str())
+            } else {
+                import_path
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_prefix("::"))
+            };
+            if let Some(segment) = rest.and_then(|rest| rest.split("::").next()) {
+                if !segments.contains(&segment) {
+                    segments.push(segment);
+                }
+            }
+        }
+        segments
+    }
+}
+
+impl ModuleEntry {
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    // Line of the item's declaration in the module's source, searched textually because the doc
+    // doesn't keep spans
+    pub fn find_declaration(&self, item: &Item) -> Option<(PathBuf, u32)> {
+        let source_path = self.source_path.as_ref()?;
+        let source = fs::read_to_string(source_path).ok()?;
+        let keyword = match item.kind {
+            ItemKind::GlobalVariable => r"var(<[^>]*>)?".to_string(),
+            kind => kind.page_prefix().to_string(),
+        };
+        let declaration = Regex::new(&format!(
+            r"^\s*(@\w+(\([^)]*\))?\s*)*{}\s+{}\b",
+            keyword,
+            regex::escape(&item.name)
+        ))
+        .unwrap();
+
+        source
+            .lines()
+            .position(|line| declaration.is_match(line))
+            .map(|line| (source_path.clone(), line as u32))
+    }
+}
+
+fn module_items(module: &Module, docs_url: Option<&str>) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut push = |name: &Ident, kind: ItemKind, signature: String, parameters: Vec<String>| {
+        if let Ident::Named(name) = name {
+            items.push(Item {
+                docs_url: docs_url
+                    .map(|url| format!("{}/{}.{}.html", url, kind.page_prefix(), name)),
+                name: name.clone(),
+                kind,
+                signature,
+                parameters,
+            });
+        }
+    };
+
+    for constant in &module.constants {
+        push(
+            &constant.name,
+            ItemKind::Constant,
+            format!(
+                "const {}: {} = {}",
+                constant.name, constant.ty, constant.init
+            ),
+            Vec::new(),
+        );
+    }
+
+    for override_ in &module.overrides {
+        let mut signature = format!("override {}: {}", override_.name, override_.ty);
+        if let Some(init) = &override_.init {
+            signature.push_str(&format!(" = {}", init));
+        }
+        push(&override_.name, ItemKind::Override, signature, Vec::new());
+    }
+
+    for var in &module.global_variables {
+        let mut signature = String::new();
+        if let Some(binding) = &var.binding {
+            signature.push_str(&format!(
+                "@group({}) @binding({}) ",
+                binding.group, binding.binding
+            ));
+        }
+        signature.push_str(&format!("var{} {}: {}", var.space, var.name, var.ty));
+        push(&var.name, ItemKind::GlobalVariable, signature, Vec::new());
+    }
+
+    for struct_ in &module.structs {
+        let mut signature = format!("struct {} {{\n", struct_.name);
+        for member in &struct_.members {
+            signature.push_str(&format!("    {}: {},\n", member.name, member.ty));
+        }
+        signature.push('}');
+        push(&struct_.name, ItemKind::Struct, signature, Vec::new());
+    }
+
+    for function in &module.functions {
+        let parameters = function
+            .arguments
+            .iter()
+            .map(|arg| format!("{}: {}", arg.name, arg.ty))
+            .collect::<Vec<_>>();
+        let mut signature = format!("fn {}({})", function.name, parameters.join(", "));
+        if let Some(ret) = &function.ret {
+            signature.push_str(&format!(" -> {}", ret));
+        }
+        push(&function.name, ItemKind::Function, signature, parameters);
+    }
+
+    items
+}
@@ -0,0 +1,95 @@
+mod analysis;
+mod index;
+mod server;
+
+use compiler::{CompilerBackend, SourceLinkProvider};
+use docs::{IndexMap, Version};
+use index::Index;
+use lsp_server::Connection;
+use std::{env, path::Path};
+
+// Usage:
+//   lsp --bevy <version> [--def <NAME>[=<value>]]... [--docs-url <url>]
+//   lsp --local <crate path>... --naga-oil <minor> [--def <NAME>[=<value>]]... [--docs-url <url>]
+//
+// The server speaks LSP over stdio, so everything else is printed to stderr.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let cache_path = Path::new("target/shader_docs_cache");
+
+    let value_of = |flag: &str| -> Result<Option<&String>, String> {
+        match args.iter().position(|arg| arg == flag) {
+            Some(idx) => match args.get(idx + 1) {
+                Some(value) => Ok(Some(value)),
+                None => Err(format!("expected a value after `{}`", flag)),
+            },
+            None => Ok(None),
+        }
+    };
+
+    let mut shader_def_values = IndexMap::new();
+    for (idx, arg) in args.iter().enumerate() {
+        if arg == "--def" {
+            let def = args
+                .get(idx + 1)
+                .ok_or("expected a shader def after `--def`")?;
+            let (key, value) = compiler::parse_shader_def(def)?;
+            shader_def_values.insert(key, value);
+        }
+    }
+
+    let input = if let Some(version) = value_of("--bevy")? {
+        let version = Version::parse(version)?;
+        let backend = CompilerBackend::for_bevy(&version)
+            .ok_or_else(|| format!("bevy {} is not supported", version))?;
+        compiler::load(
+            "bevy",
+            version,
+            |name| name.starts_with("bevy"),
+            shader_def_values,
+            cache_path,
+            backend,
+            &SourceLinkProvider::DocsRs,
+        )?
+    } else if let Some(idx) = args.iter().position(|arg| arg == "--local") {
+        let crate_paths = args[idx + 1..]
+            .iter()
+            .take_while(|arg| !arg.starts_with("--"))
+            .map(Path::new)
+            .collect::<Vec<_>>();
+        let minor = value_of("--naga-oil")?.ok_or("`--local` needs `--naga-oil <minor>`")?;
+        let backend = CompilerBackend::for_naga_oil(minor.parse()?)
+            .ok_or_else(|| format!("naga_oil 0.{} is not supported", minor))?;
+        compiler::load_local(
+            "local",
+            Version::new(0, 0, 0),
+            &crate_paths,
+            shader_def_values,
+            backend,
+            &SourceLinkProvider::DocsRs,
+        )?
+    } else {
+        return Err("expected `--bevy <version>` or `--local <crate path>...`".into());
+    };
+
+    let source_paths = input.source_paths();
+    let doc = input.compile()?;
+    let index = Index::new(
+        &doc,
+        &source_paths,
+        value_of("--docs-url")?.map(String::as_str),
+    );
+    eprintln!(
+        "Serving {} modules of {} {}",
+        index.modules.len(),
+        doc.root.name,
+        doc.version
+    );
+
+    let (connection, io_threads) = Connection::stdio();
+    server::run(&connection, &index)?;
+    drop(connection);
+    io_threads.join()?;
+
+    Ok(())
+}
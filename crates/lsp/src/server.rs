@@ -0,0 +1,303 @@
+use crate::{
+    analysis::{self, Target},
+    index::{Index, ItemKind},
+};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as NotificationTrait,
+    },
+    request::{
+        Completion, GotoDefinition, HoverRequest, Request as RequestTrait, SignatureHelpRequest,
+    },
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    Documentation, GotoDefinitionParams, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf, ParameterInformation,
+    ParameterLabel, Position, Range, ServerCapabilities, SignatureHelp, SignatureHelpOptions,
+    SignatureHelpParams, SignatureInformation, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Url,
+};
+use std::{collections::HashMap, fs, path::Path};
+
+pub fn run(connection: &Connection, index: &Index) -> Result<(), Box<dyn std::error::Error>> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".to_string()]),
+            ..Default::default()
+        }),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut documents = HashMap::new();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = handle_request(index, &documents, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                handle_notification(&mut documents, notification)?
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_notification(
+    documents: &mut HashMap<Url, String>,
+    notification: Notification,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+            documents.insert(params.text_document.uri, params.text_document.text);
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+            // Only full syncs are announced, so the last change has the whole text
+            if let Some(change) = params.content_changes.into_iter().last() {
+                documents.insert(params.text_document.uri, change.text);
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+            documents.remove(&params.text_document.uri);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_request(index: &Index, documents: &HashMap<Url, String>, request: Request) -> Response {
+    let result = match request.method.as_str() {
+        HoverRequest::METHOD => serde_json::from_value(request.params)
+            .map(|params| serde_json::to_value(hover(index, documents, params))),
+        GotoDefinition::METHOD => serde_json::from_value(request.params)
+            .map(|params| serde_json::to_value(definition(index, documents, params))),
+        Completion::METHOD => serde_json::from_value(request.params)
+            .map(|params| serde_json::to_value(completion(index, documents, params))),
+        SignatureHelpRequest::METHOD => serde_json::from_value(request.params)
+            .map(|params| serde_json::to_value(signature_help(index, documents, params))),
+        method => {
+            return Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {}", method),
+            )
+        }
+    };
+
+    match result {
+        Ok(Ok(result)) => Response {
+            id: request.id,
+            result: Some(result),
+            error: None,
+        },
+        Ok(Err(err)) | Err(err) => {
+            Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string())
+        }
+    }
+}
+
+// Documents that weren't opened are read from disk
+fn document_text(documents: &HashMap<Url, String>, uri: &Url) -> Option<String> {
+    match documents.get(uri) {
+        Some(text) => Some(text.clone()),
+        None => fs::read_to_string(uri.to_file_path().ok()?).ok(),
+    }
+}
+
+fn hover(index: &Index, documents: &HashMap<Url, String>, params: HoverParams) -> Option<Hover> {
+    let position = params.text_document_position_params;
+    let text = document_text(documents, &position.text_document.uri)?;
+    let imports = analysis::parse_imports(&text, index);
+    let path = analysis::path_at(&text, position.position)?;
+
+    let (signature, import_path, docs_url) = match analysis::resolve(&path, &imports, index)? {
+        Target::Module(module) => (
+            format!("#define_import_path {}", module.import_path),
+            None,
+            &module.docs_url,
+        ),
+        Target::Item(module, item) => (
+            item.signature.clone(),
+            Some(&module.import_path),
+            &item.docs_url,
+        ),
+    };
+
+    let mut value = format!("```wgsl\n{}\n```", signature);
+    if let Some(import_path) = import_path {
+        value.push_str(&format!("\n\n`{}`", import_path));
+    }
+    if let Some(docs_url) = docs_url {
+        value.push_str(&format!("\n\n[Documentation]({})", docs_url));
+    }
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: None,
+    })
+}
+
+fn definition(
+    index: &Index,
+    documents: &HashMap<Url, String>,
+    params: GotoDefinitionParams,
+) -> Option<Location> {
+    let position = params.text_document_position_params;
+    let text = document_text(documents, &position.text_document.uri)?;
+    let imports = analysis::parse_imports(&text, index);
+    let path = analysis::path_at(&text, position.position)?;
+
+    let (source_path, line) = match analysis::resolve(&path, &imports, index)? {
+        Target::Module(module) => (module.source_path.clone()?, 0),
+        Target::Item(module, item) => module.find_declaration(item)?,
+    };
+
+    let position = Position::new(line, 0);
+    Some(Location {
+        uri: file_url(&source_path)?,
+        range: Range::new(position, position),
+    })
+}
+
+fn completion(
+    index: &Index,
+    documents: &HashMap<Url, String>,
+    params: CompletionParams,
+) -> Option<Vec<CompletionItem>> {
+    let position = params.text_document_position;
+    let text = document_text(documents, &position.text_document.uri)?;
+    let imports = analysis::parse_imports(&text, index);
+    let (typed, in_import) = analysis::completion_context(&text, position.position);
+
+    let module_item = |label: &str| CompletionItem {
+        label: label.to_string(),
+        kind: Some(CompletionItemKind::MODULE),
+        ..Default::default()
+    };
+
+    let mut items = Vec::new();
+    match typed.rsplit_once("::") {
+        Some((parent, _)) => {
+            // Import paths are always fully qualified, elsewhere the first segment may be an alias
+            let mut parent = parent.to_string();
+            if !in_import {
+                if let Some((first, rest)) = parent.split_once("::") {
+                    if let Some(import_path) = imports.modules.get(first) {
+                        parent = format!("{}::{}", import_path, rest);
+                    }
+                } else if let Some(import_path) = imports.modules.get(&parent) {
+                    parent = import_path.clone();
+                }
+            }
+
+            if let Some(module) = index.module(&parent) {
+                items.extend(module.items.iter().map(|item| CompletionItem {
+                    label: item.name.clone(),
+                    kind: Some(completion_kind(item.kind)),
+                    detail: Some(item.signature.clone()),
+                    ..Default::default()
+                }));
+            }
+            items.extend(index.child_segments(&parent).into_iter().map(module_item));
+        }
+        None if in_import => {
+            items.extend(index.child_segments("").into_iter().map(module_item));
+        }
+        None => {
+            let mut names = imports.items.iter().collect::<Vec<_>>();
+            names.sort();
+            for (name, (module_path, item)) in names {
+                if let Some(item) = index.module(module_path).and_then(|m| m.item(item)) {
+                    items.push(CompletionItem {
+                        label: name.clone(),
+                        kind: Some(completion_kind(item.kind)),
+                        detail: Some(item.signature.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+
+            let mut aliases = imports.modules.keys().collect::<Vec<_>>();
+            aliases.sort();
+            items.extend(aliases.into_iter().map(|alias| module_item(alias)));
+        }
+    }
+
+    Some(items)
+}
+
+fn signature_help(
+    index: &Index,
+    documents: &HashMap<Url, String>,
+    params: SignatureHelpParams,
+) -> Option<SignatureHelp> {
+    let position = params.text_document_position_params;
+    let text = document_text(documents, &position.text_document.uri)?;
+    let imports = analysis::parse_imports(&text, index);
+    let (path, active_parameter) = analysis::call_at(&text, position.position)?;
+
+    let Target::Item(_, item) = analysis::resolve(&path, &imports, index)? else {
+        return None;
+    };
+    if item.kind != ItemKind::Function {
+        return None;
+    }
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label: item.signature.clone(),
+            documentation: item.docs_url.as_ref().map(|docs_url| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: format!("[Documentation]({})", docs_url),
+                })
+            }),
+            parameters: Some(
+                item.parameters
+                    .iter()
+                    .map(|parameter| ParameterInformation {
+                        label: ParameterLabel::Simple(parameter.clone()),
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
+
+fn completion_kind(kind: ItemKind) -> CompletionItemKind {
+    match kind {
+        ItemKind::Constant | ItemKind::Override => CompletionItemKind::CONSTANT,
+        ItemKind::GlobalVariable => CompletionItemKind::VARIABLE,
+        ItemKind::Struct => CompletionItemKind::STRUCT,
+        ItemKind::Function => CompletionItemKind::FUNCTION,
+    }
+}
+
+fn file_url(path: &Path) -> Option<Url> {
+    Url::from_file_path(fs::canonicalize(path).ok()?).ok()
+}
@@ -0,0 +1,317 @@
+// Runs the server on the compiler's fixture crates and drives it with scripted LSP sessions over
+// stdio
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+const DOCUMENT: &str = "file:///workspace/shader.wgsl";
+
+// Line 4 calls a function from a fully qualified path, line 5 one through a module alias
+const SOURCE: &str = "\
+#import fixture_core::types::{Instance, AMBIENT}
+#import fixture_core::utils as utils
+
+fn shade(instance: Instance) -> f32 {
+    let color = fixture_render::mesh::shade(out, true);
+    return utils::luminance(AMBIENT);
+}
+";
+
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl Session {
+    fn start(extra_args: &[&str]) -> Self {
+        let fixtures_path = fixtures_path();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_lsp"))
+            .arg("--local")
+            .arg(fixtures_path.join("fixture_core"))
+            .arg(fixtures_path.join("fixture_render"))
+            .args([
+                "--naga-oil",
+                "20",
+                "--def",
+                "SHADOWS",
+                "--def",
+                "MAX_STEPS=8u",
+            ])
+            .args(extra_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut session = Session {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            next_id: 0,
+        };
+
+        let capabilities = session.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(capabilities["capabilities"]["hoverProvider"], json!(true));
+        session.notify("initialized", json!({}));
+        session.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": DOCUMENT,
+                    "languageId": "wgsl",
+                    "version": 1,
+                    "text": SOURCE,
+                }
+            }),
+        );
+        session
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            self.stdout.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = Some(value.parse().unwrap());
+            }
+        }
+
+        let mut body = vec![0; length.expect("missing Content-Length")];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+
+        // Notifications the server sends on its own are skipped
+        loop {
+            let message = self.receive();
+            if message["id"] == json!(id) {
+                assert!(message.get("error").is_none(), "{method} failed: {message}");
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    fn at(&mut self, method: &str, line: u32, character: u32) -> Value {
+        self.request(
+            method,
+            json!({
+                "textDocument": { "uri": DOCUMENT },
+                "position": { "line": line, "character": character },
+            }),
+        )
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+fn fixtures_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../compiler/tests/fixtures")
+}
+
+// Position of the first occurrence of `pattern` in the test document, plus `offset` characters
+fn find(pattern: &str, offset: u32) -> (u32, u32) {
+    SOURCE
+        .lines()
+        .enumerate()
+        .find_map(|(line, text)| {
+            text.find(pattern)
+                .map(|character| (line as u32, character as u32 + offset))
+        })
+        .unwrap()
+}
+
+fn labels(completion: &Value) -> Vec<&str> {
+    completion
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect()
+}
+
+// Line of the first line of `file` starting with `prefix`
+fn source_line(file: &str, prefix: &str) -> u32 {
+    fs::read_to_string(fixtures_path().join(file))
+        .unwrap()
+        .lines()
+        .position(|line| line.starts_with(prefix))
+        .unwrap() as u32
+}
+
+#[test]
+fn hover() {
+    let mut session = Session::start(&["--docs-url", "https://example.com/docs"]);
+
+    let (line, character) = find("luminance", 2);
+    let hover = session.at("textDocument/hover", line, character);
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(
+        value.contains("fn luminance(color: vec3<f32>) -> f32"),
+        "{value}"
+    );
+    assert!(value.contains("`fixture_core::utils`"), "{value}");
+    assert!(
+        value.contains(
+            "(https://example.com/docs/local/0.0.0/local/fixture_core/utils/fn.luminance.html)"
+        ),
+        "{value}"
+    );
+
+    // Hovering the alias shows the module
+    let (line, character) = find("utils::luminance", 1);
+    let hover = session.at("textDocument/hover", line, character);
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(
+        value.contains("#define_import_path fixture_core::utils"),
+        "{value}"
+    );
+
+    // Items imported by name
+    let (line, character) = find("AMBIENT)", 0);
+    let hover = session.at("textDocument/hover", line, character);
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(value.contains("const AMBIENT: vec3<f32>"), "{value}");
+
+    // Nothing is known about local variables
+    let (line, character) = find("color", 0);
+    assert_eq!(
+        session.at("textDocument/hover", line, character),
+        Value::Null
+    );
+
+    session.shutdown();
+}
+
+#[test]
+fn definition() {
+    let mut session = Session::start(&[]);
+
+    let (line, character) = find("Instance)", 3);
+    let location = session.at("textDocument/definition", line, character);
+    assert!(location["uri"]
+        .as_str()
+        .unwrap()
+        .ends_with("fixture_core/src/types.wgsl"));
+    assert_eq!(
+        location["range"]["start"]["line"],
+        json!(source_line(
+            "fixture_core/src/types.wgsl",
+            "struct Instance "
+        ))
+    );
+
+    let (line, character) = find("shade(out", 0);
+    let location = session.at("textDocument/definition", line, character);
+    assert!(location["uri"]
+        .as_str()
+        .unwrap()
+        .ends_with("fixture_render/src/mesh.wgsl"));
+    assert_eq!(
+        location["range"]["start"]["line"],
+        json!(source_line("fixture_render/src/mesh.wgsl", "fn shade("))
+    );
+
+    // Modules resolve to their file
+    let (line, character) = find("mesh::", 0);
+    let location = session.at("textDocument/definition", line, character);
+    assert!(location["uri"]
+        .as_str()
+        .unwrap()
+        .ends_with("fixture_render/src/mesh.wgsl"));
+
+    session.shutdown();
+}
+
+#[test]
+fn completion() {
+    let mut session = Session::start(&[]);
+
+    session.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": DOCUMENT, "version": 2 },
+            "contentChanges": [{
+                "text": "#import fixture_core::utils as utils\n#import fixture_core::types::Light\n    utils::\n    Li\n#import fixture_core::\n#import fixture_core::types::{Instance, \n",
+            }],
+        }),
+    );
+
+    let completion = session.at("textDocument/completion", 4, 22);
+    assert_eq!(labels(&completion), ["bindings", "types", "utils"]);
+
+    let completion = session.at("textDocument/completion", 5, 40);
+    let labels_ = labels(&completion);
+    assert!(labels_.contains(&"AMBIENT"), "{labels_:?}");
+    assert!(labels_.contains(&"Lights"), "{labels_:?}");
+
+    let completion = session.at("textDocument/completion", 2, 11);
+    let labels_ = labels(&completion);
+    assert!(labels_.contains(&"luminance"), "{labels_:?}");
+    assert!(labels_.contains(&"saturate_in_place"), "{labels_:?}");
+    let luminance = completion
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["label"] == "luminance")
+        .unwrap();
+    assert_eq!(luminance["detail"], "fn luminance(color: vec3<f32>) -> f32");
+
+    // Outside of paths the imported names are offered, including those of unfinished imports
+    let completion = session.at("textDocument/completion", 3, 6);
+    assert_eq!(labels(&completion), ["Instance", "Light", "utils"]);
+
+    session.shutdown();
+}
+
+#[test]
+fn signature_help() {
+    let mut session = Session::start(&[]);
+
+    let (line, character) = find("true);", 0);
+    let help = session.at("textDocument/signatureHelp", line, character);
+    assert_eq!(
+        help["signatures"][0]["label"],
+        "fn shade(in: VertexOutput, front_facing: bool) -> vec4<f32>"
+    );
+    assert_eq!(help["activeParameter"], 1);
+
+    let (line, character) = find("AMBIENT)", 0);
+    let help = session.at("textDocument/signatureHelp", line, character);
+    assert_eq!(
+        help["signatures"][0]["parameters"][0]["label"],
+        "color: vec3<f32>"
+    );
+    assert_eq!(help["activeParameter"], 0);
+
+    session.shutdown();
+}
@@ -1,204 +1,597 @@
-use docs::{IndexMap, ShaderDefValue, Version};
-use std::path::Path;
+mod serve;
+
+use compiler::{CompilerBackend, ForgeKind, SourceLinkProvider};
+use docs::{Doc, IndexMap, ShaderDefValue, Version};
+use generator::{ArchiveFormat, ArchiveSink, FsSink, OutputSink, Retention, RustStyle};
+use serde_json::{json, Map, Value};
+use std::{
+    env, fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+// Number of versions downloaded and compiled at the same time
+const COMPILE_WORKERS: usize = 4;
+
+struct Target {
+    // Root crate of the docs, several crates can share a site
+    name: &'static str,
+    version: Version,
+    // Crates whose shaders are compiled, including the ones of the dependencies
+    shader_crates: fn(&str) -> bool,
+    shader_def_values: fn() -> IndexMap<String, ShaderDefValue>,
+    backend: CompilerBackend,
+    source_links: SourceLinkProvider,
+    // Other targets whose modules are imported, e.g. the bevy release of a plugin. Their modules
+    // link to the docs of that target instead of being documented again.
+    dependencies: Vec<(&'static str, Version)>,
+}
+
+impl Target {
+    fn bevy(
+        version: Version,
+        shader_def_values: fn() -> IndexMap<String, ShaderDefValue>,
+        backend: CompilerBackend,
+    ) -> Self {
+        Target {
+            name: "bevy",
+            version,
+            shader_crates: |name| name.starts_with("bevy"),
+            shader_def_values,
+            backend,
+            source_links: SourceLinkProvider::DocsRs,
+            dependencies: Vec::new(),
+        }
+    }
+
+    fn load(&self, cache_path: &Path) -> Result<compiler::Input, Box<dyn std::error::Error>> {
+        compiler::load(
+            self.name,
+            self.version.clone(),
+            self.shader_crates,
+            (self.shader_def_values)(),
+            cache_path,
+            self.backend,
+            &self.source_links,
+        )
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Compile docs
-    let cache_path = Path::new("target/shader_docs_cache");
-    let docs = vec![
-        compiler::compile(
-            "bevy",
+    let targets = vec![
+        Target::bevy(
             Version::new(0, 19, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_19(),
-            cache_path,
-            compiler::CompilerBackend::V0_22,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_19,
+            CompilerBackend::V0_22,
+        ),
+        Target::bevy(
             Version::new(0, 18, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_18(),
-            cache_path,
-            compiler::CompilerBackend::V0_20,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_18,
+            CompilerBackend::V0_20,
+        ),
+        Target::bevy(
             Version::new(0, 18, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_18(),
-            cache_path,
-            compiler::CompilerBackend::V0_20,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_18,
+            CompilerBackend::V0_20,
+        ),
+        Target::bevy(
             Version::new(0, 17, 3),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_17(),
-            cache_path,
-            compiler::CompilerBackend::V0_19,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_17,
+            CompilerBackend::V0_19,
+        ),
+        Target::bevy(
             Version::new(0, 17, 2),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_17(),
-            cache_path,
-            compiler::CompilerBackend::V0_19,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_17,
+            CompilerBackend::V0_19,
+        ),
+        Target::bevy(
             Version::new(0, 17, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_17(),
-            cache_path,
-            compiler::CompilerBackend::V0_19,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_17,
+            CompilerBackend::V0_19,
+        ),
+        Target::bevy(
             Version::new(0, 17, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_17(),
-            cache_path,
-            compiler::CompilerBackend::V0_19,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_17,
+            CompilerBackend::V0_19,
+        ),
+        Target::bevy(
             Version::new(0, 16, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_16(),
-            cache_path,
-            compiler::CompilerBackend::V0_17,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_16,
+            CompilerBackend::V0_17,
+        ),
+        Target::bevy(
             Version::new(0, 16, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_16(),
-            cache_path,
-            compiler::CompilerBackend::V0_17,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_16,
+            CompilerBackend::V0_17,
+        ),
+        Target::bevy(
             Version::new(0, 15, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_15(),
-            cache_path,
-            compiler::CompilerBackend::V0_16,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_15,
+            CompilerBackend::V0_16,
+        ),
+        Target::bevy(
             Version::new(0, 15, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_15(),
-            cache_path,
-            compiler::CompilerBackend::V0_16,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_15,
+            CompilerBackend::V0_16,
+        ),
+        Target::bevy(
             Version::new(0, 14, 2),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_14(),
-            cache_path,
-            compiler::CompilerBackend::V0_14,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_14,
+            CompilerBackend::V0_14,
+        ),
+        Target::bevy(
             Version::new(0, 14, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_14(),
-            cache_path,
-            compiler::CompilerBackend::V0_14,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_14,
+            CompilerBackend::V0_14,
+        ),
+        Target::bevy(
             Version::new(0, 14, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_14(),
-            cache_path,
-            compiler::CompilerBackend::V0_14,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_14,
+            CompilerBackend::V0_14,
+        ),
+        Target::bevy(
             Version::new(0, 13, 2),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_13(),
-            cache_path,
-            compiler::CompilerBackend::V0_13,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_13,
+            CompilerBackend::V0_13,
+        ),
+        Target::bevy(
             Version::new(0, 13, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_13(),
-            cache_path,
-            compiler::CompilerBackend::V0_13,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_13,
+            CompilerBackend::V0_13,
+        ),
+        Target::bevy(
             Version::new(0, 13, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_13(),
-            cache_path,
-            compiler::CompilerBackend::V0_13,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_13,
+            CompilerBackend::V0_13,
+        ),
+        Target::bevy(
             Version::new(0, 12, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_12(),
-            cache_path,
-            compiler::CompilerBackend::V0_10,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_12,
+            CompilerBackend::V0_10,
+        ),
+        Target::bevy(
             Version::new(0, 12, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_12(),
-            cache_path,
-            compiler::CompilerBackend::V0_10,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_12,
+            CompilerBackend::V0_10,
+        ),
+        Target::bevy(
             Version::new(0, 11, 3),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_11(),
-            cache_path,
-            compiler::CompilerBackend::V0_08,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_11,
+            CompilerBackend::V0_08,
+        ),
+        Target::bevy(
             Version::new(0, 11, 2),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_11(),
-            cache_path,
-            compiler::CompilerBackend::V0_08,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_11,
+            CompilerBackend::V0_08,
+        ),
+        Target::bevy(
             Version::new(0, 11, 1),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_11(),
-            cache_path,
-            compiler::CompilerBackend::V0_08,
-        )?,
-        compiler::compile(
-            "bevy",
+            shader_def_values_0_11,
+            CompilerBackend::V0_08,
+        ),
+        Target::bevy(
             Version::new(0, 11, 0),
-            |name| name.starts_with("bevy"),
-            shader_def_values_0_11(),
+            shader_def_values_0_11,
+            CompilerBackend::V0_08,
+        ),
+    ];
+
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let cache_path = Path::new("target/shader_docs_cache");
+    let site_path = Path::new("target/shader_docs");
+
+    // `serve` previews the docs on localhost and rebuilds local crates when they change
+    if args.first().map(String::as_str) == Some("serve") {
+        return serve::run(&args[1..], &targets, cache_path);
+    }
+
+    // `--wgsl-analyzer <version>` writes the wgsl-analyzer settings of that version instead
+    if let Some(idx) = args.iter().position(|arg| arg == "--wgsl-analyzer") {
+        let version = args
+            .get(idx + 1)
+            .ok_or("expected a version after `--wgsl-analyzer`")?;
+        return write_wgsl_analyzer_settings(
+            &targets,
+            &Version::parse(version)?,
+            cache_path,
+            Path::new("target/wgsl_analyzer"),
+        );
+    }
+
+    // `--docset <version> --feed-url <url>` packages that version for Dash and Zeal instead
+    if let Some(idx) = args.iter().position(|arg| arg == "--docset") {
+        let version = args
+            .get(idx + 1)
+            .ok_or("expected a version after `--docset`")?;
+        let feed_url = args
+            .iter()
+            .position(|arg| arg == "--feed-url")
+            .and_then(|idx| args.get(idx + 1))
+            .ok_or("`--docset` needs `--feed-url <url>` where the archives are hosted")?;
+        return write_docset(
+            &targets,
+            &Version::parse(version)?,
             cache_path,
-            compiler::CompilerBackend::V0_08,
+            Path::new("target/docsets"),
+            feed_url,
+        );
+    }
+
+    // `--markdown <version>` writes that version as the sources of an mdBook instead
+    if let Some(idx) = args.iter().position(|arg| arg == "--markdown") {
+        let version = args
+            .get(idx + 1)
+            .ok_or("expected a version after `--markdown`")?;
+        return write_markdown(
+            &targets,
+            &Version::parse(version)?,
+            cache_path,
+            Path::new("target/shader_docs_md"),
+        );
+    }
+
+    // `--rust <encase|bytemuck> <version>` writes the structs and constants of that version as
+    // Rust types instead
+    if let Some(idx) = args.iter().position(|arg| arg == "--rust") {
+        let style = RustStyle::parse(
+            args.get(idx + 1)
+                .ok_or("expected `encase` or `bytemuck` after `--rust`")?,
+        )?;
+        let version = args
+            .get(idx + 2)
+            .ok_or("expected a version after the style of `--rust`")?;
+        return write_rust(
+            &targets,
+            &Version::parse(version)?,
+            style,
+            cache_path,
+            Path::new("target/shader_types"),
+        );
+    }
+
+    // `--wgsl-stubs <version>` writes a WGSL interface stub per shader module of that version
+    // instead
+    if let Some(idx) = args.iter().position(|arg| arg == "--wgsl-stubs") {
+        let version = args
+            .get(idx + 1)
+            .ok_or("expected a version after `--wgsl-stubs`")?;
+        return write_wgsl_stubs(
+            &targets,
+            &Version::parse(version)?,
+            cache_path,
+            Path::new("target/wgsl_stubs"),
+        );
+    }
+
+    // `--retain <all|latest-patch>` removes the versions that aren't kept from the site
+    let retention = match args.iter().position(|arg| arg == "--retain") {
+        Some(idx) => Retention::parse(
+            args.get(idx + 1)
+                .ok_or("expected a policy after `--retain`")?,
         )?,
-    ];
+        None => Retention::All,
+    };
+
+    // `--archive <path>` packs the whole site into a `.zip` or `.tar.gz` instead
+    if let Some(idx) = args.iter().position(|arg| arg == "--archive") {
+        let archive_path = Path::new(
+            args.get(idx + 1)
+                .ok_or("expected a path after `--archive`")?,
+        );
+        let format = match archive_path.to_str() {
+            Some(path) if path.ends_with(".zip") => ArchiveFormat::Zip,
+            Some(path) if path.ends_with(".tar.gz") || path.ends_with(".tgz") => {
+                ArchiveFormat::TarGz
+            }
+            _ => return Err("the archive must end with `.zip`, `.tar.gz` or `.tgz`".into()),
+        };
+
+        // The archive starts out empty, so every version is compiled
+        let sink = ArchiveSink::new(format);
+        let docs = compile_targets(&targets, cache_path, &sink, true)?;
+        generator::generate_all(&docs, &sink, retention)?;
+        sink.finish(archive_path)?;
+        println!("Wrote {}", archive_path.display());
+        return Ok(());
+    }
+
+    // `--force` regenerates versions even if their inputs didn't change
+    let force = args.iter().any(|arg| arg == "--force");
+
+    // `--git <path> [--revision <rev>]` adds a snapshot of a bevy checkout or archive to the site
+    if let Some(idx) = args.iter().position(|arg| arg == "--git") {
+        let repository_path = Path::new(
+            args.get(idx + 1)
+                .ok_or("expected a repository path after `--git`")?,
+        );
+        let revision = match args.iter().position(|arg| arg == "--revision") {
+            Some(idx) => args
+                .get(idx + 1)
+                .ok_or("expected a revision after `--revision`")?,
+            None => "HEAD",
+        };
+
+        let sink = FsSink::new(site_path);
+        let Some(doc) = compile_snapshot(
+            &targets[0],
+            repository_path,
+            revision,
+            cache_path,
+            &sink,
+            force,
+        )?
+        else {
+            return Ok(());
+        };
+        generator::generate_all(&[doc], &sink, retention)?;
+        return Ok(());
+    }
+
+    // Compile docs
+    let sink = FsSink::new(site_path);
+    let docs = compile_targets(&targets, cache_path, &sink, force)?;
 
     // Generate docs
-    for doc in docs {
-        generator::generate(&doc, Path::new("target/shader_docs"))?;
+    generator::generate_all(&docs, &sink, retention)?;
+
+    Ok(())
+}
+
+// Compiles the targets on a bounded pool of workers, the docs are returned in the order of the
+// targets regardless of which worker finished first. Targets that were already generated from
+// the same inputs are skipped unless `force` is set.
+fn compile_targets(
+    targets: &[Target],
+    cache_path: &Path,
+    sink: &dyn OutputSink,
+    force: bool,
+) -> Result<Vec<Doc>, Box<dyn std::error::Error>> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..targets.len()).map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+        for _ in 0..COMPILE_WORKERS.min(targets.len()) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(target) = targets.get(idx) else {
+                    break;
+                };
+
+                // Errors are not Send, so they cross the thread boundary as strings
+                let result = compile_target(target, cache_path, sink, force)
+                    .map_err(|err| format!("{} {}: {}", target.name, target.version, err));
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+
+    let mut docs = Vec::new();
+    for doc in results.into_inner().unwrap() {
+        docs.push(doc.unwrap()?);
+    }
+
+    // Dependencies are linked once everything is compiled, the ones that were skipped as unchanged
+    // are compiled for their modules
+    for (idx, target) in targets.iter().enumerate() {
+        let Some(mut doc) = docs[idx].take() else {
+            continue;
+        };
+        for (name, version) in &target.dependencies {
+            let dependency_idx = targets
+                .iter()
+                .position(|dependency| dependency.name == *name && dependency.version == *version)
+                .ok_or_else(|| {
+                    format!(
+                        "{} {} depends on {} {}, which is not a target",
+                        target.name, target.version, name, version
+                    )
+                })?;
+            match &docs[dependency_idx] {
+                Some(dependency) => compiler::link_dependency(&mut doc, dependency),
+                None => {
+                    let dependency = targets[dependency_idx].load(cache_path)?.compile()?;
+                    compiler::link_dependency(&mut doc, &dependency);
+                }
+            }
+        }
+        docs[idx] = Some(doc);
     }
 
+    Ok(docs.into_iter().flatten().collect())
+}
+
+// The bevy target of `version`, the modes that export a single version only take bevy's
+fn bevy_target<'a>(
+    targets: &'a [Target],
+    version: &Version,
+) -> Result<&'a Target, Box<dyn std::error::Error>> {
+    targets
+        .iter()
+        .find(|target| target.name == "bevy" && &target.version == version)
+        .ok_or_else(|| format!("bevy {} is not a documented version", version).into())
+}
+
+// Writes `bevy-{version}.json` with the settings wgsl-analyzer needs to resolve bevy's imports,
+// to be merged into the editor's settings
+fn write_wgsl_analyzer_settings(
+    targets: &[Target],
+    version: &Version,
+    cache_path: &Path,
+    out_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target = bevy_target(targets, version)?;
+
+    let input = target.load(cache_path)?;
+    let custom_imports = input
+        .custom_imports()
+        .into_iter()
+        .map(|(import_path, source)| (import_path, Value::String(source)))
+        .collect::<Map<_, _>>();
+    let doc = input.compile()?;
+
+    // wgsl-analyzer only knows whether a def is set, not its value
+    let shader_defs = doc
+        .compiled_with
+        .iter()
+        .filter(|(_, value)| !matches!(value, ShaderDefValue::Bool(false)))
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+
+    let settings = json!({
+        "wgsl-analyzer.customImports": custom_imports,
+        "wgsl-analyzer.shaderDefs": shader_defs,
+    });
+
+    fs::create_dir_all(out_path)?;
+    let path = out_path.join(format!("{}-{}.json", target.name, version));
+    fs::write(&path, serde_json::to_string_pretty(&settings)?)?;
+    println!("Wrote {}", path.display());
+
+    Ok(())
+}
+
+// Writes the docset of a version to `{out_path}/{version}/bevy.docset`, its archive and the feed
+fn write_docset(
+    targets: &[Target],
+    version: &Version,
+    cache_path: &Path,
+    out_path: &Path,
+    feed_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target = bevy_target(targets, version)?;
+
+    let doc = target.load(cache_path)?.compile()?;
+
+    fs::create_dir_all(out_path)?;
+    generator::generate_docset(&doc, out_path, feed_url)?;
+    println!("Wrote {}", out_path.join(version.to_string()).display());
+
+    Ok(())
+}
+
+// Writes the Markdown pages and `SUMMARY.md` of a version to `{out_path}/{version}/src`, next to
+// a `book.toml`
+fn write_markdown(
+    targets: &[Target],
+    version: &Version,
+    cache_path: &Path,
+    out_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target = bevy_target(targets, version)?;
+
+    let doc = target.load(cache_path)?.compile()?;
+
+    let book_path = out_path.join(version.to_string());
+    fs::remove_dir_all(&book_path).ok();
+    generator::generate_markdown(&doc, &book_path.join("src"))?;
+    fs::write(
+        book_path.join("book.toml"),
+        format!("[book]\ntitle = \"{} {} shaders\"\n", target.name, version),
+    )?;
+    println!("Wrote {}", book_path.display());
+
+    Ok(())
+}
+
+// Writes the types of a version to `{out_path}/bevy-{version}-{style}.rs`
+fn write_rust(
+    targets: &[Target],
+    version: &Version,
+    style: RustStyle,
+    cache_path: &Path,
+    out_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target = bevy_target(targets, version)?;
+
+    let doc = target.load(cache_path)?.compile()?;
+
+    let style_name = match style {
+        RustStyle::Encase => "encase",
+        RustStyle::Bytemuck => "bytemuck",
+    };
+    let path = out_path.join(format!("{}-{}-{}.rs", target.name, version, style_name));
+    generator::generate_rust(&doc, &path, style)?;
+    println!("Wrote {}", path.display());
+
+    Ok(())
+}
+
+// Writes the stubs of a version below `{out_path}/bevy-{version}`, at the import path of every
+// module
+fn write_wgsl_stubs(
+    targets: &[Target],
+    version: &Version,
+    cache_path: &Path,
+    out_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target = bevy_target(targets, version)?;
+
+    let doc = target.load(cache_path)?.compile()?;
+
+    let stubs_path = out_path.join(format!("{}-{}", target.name, version));
+    fs::remove_dir_all(&stubs_path).ok();
+    generator::generate_wgsl(&doc, &stubs_path)?;
+    println!("Wrote {}", stubs_path.display());
+
     Ok(())
 }
 
+fn compile_target(
+    target: &Target,
+    cache_path: &Path,
+    sink: &dyn OutputSink,
+    force: bool,
+) -> Result<Option<Doc>, Box<dyn std::error::Error>> {
+    let input = target.load(cache_path)?;
+
+    if !force && generator::is_generated(sink, target.name, &target.version, &input.hash()) {
+        println!("Skipping {} {}, unchanged", target.name, target.version);
+        return Ok(None);
+    }
+
+    Ok(Some(input.compile()?))
+}
+
+// Snapshots are ahead of the newest release, so they are compiled like it
+fn compile_snapshot(
+    newest: &Target,
+    repository_path: &Path,
+    revision: &str,
+    cache_path: &Path,
+    sink: &dyn OutputSink,
+    force: bool,
+) -> Result<Option<Doc>, Box<dyn std::error::Error>> {
+    // docs.rs only has releases, snapshots link to their commit on GitHub instead
+    let source_links = match &newest.source_links {
+        SourceLinkProvider::DocsRs => SourceLinkProvider::Forge {
+            kind: ForgeKind::GitHub,
+            tag_pattern: "v{version}".to_string(),
+        },
+        source_links => source_links.clone(),
+    };
+
+    let snapshot = compiler::extract_snapshot(repository_path, revision, cache_path)?;
+    let input = compiler::load_git(
+        newest.name,
+        &snapshot,
+        newest.shader_crates,
+        (newest.shader_def_values)(),
+        newest.backend,
+        &source_links,
+    )?;
+
+    if !force && generator::is_generated(sink, newest.name, input.version(), &input.hash()) {
+        println!("Skipping {} {}, unchanged", newest.name, input.version());
+        return Ok(None);
+    }
+
+    Ok(Some(input.compile()?))
+}
+
 // TODO: More shader defs available on 0.19
 fn shader_def_values_0_19() -> IndexMap<String, ShaderDefValue> {
     use ShaderDefValue::*;
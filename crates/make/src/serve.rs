@@ -0,0 +1,394 @@
+use crate::{compile_targets, Target};
+use compiler::{CompilerBackend, SourceLinkProvider};
+use docs::{IndexMap, ShaderDefValue, Version};
+use generator::{FsSink, Retention};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+// Interval in which the watched files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Interval in which idle live reload connections are pinged, which also notices closed tabs
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+const LIVE_RELOAD_PATH: &str = "/__live_reload";
+
+// Crates that are compiled into one doc, named after the first crate
+struct LocalDoc {
+    name: String,
+    crate_paths: Vec<PathBuf>,
+}
+
+// Counts the rebuilds, open pages reload when it differs from the one they were served with
+#[derive(Default)]
+struct Generation {
+    value: Mutex<u64>,
+    changed: Condvar,
+}
+
+// Usage:
+//   make serve [--port <port>]
+//   make serve --local <crate path>... [--local <crate path>...]... --naga-oil <minor>
+//              [--def <NAME>[=<value>]]... [--defs <file>] [--port <port>]
+//
+// Without `--local` the bevy docs are built once and served. With it every `--local` group is
+// compiled into its own doc, and the doc is rebuilt whenever one of its shaders changes. The
+// `--defs` file holds one shader def per line and rebuilds all docs when it changes.
+pub fn run(
+    args: &[String],
+    targets: &[Target],
+    cache_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let value_of = |flag: &str| -> Result<Option<&String>, String> {
+        match args.iter().position(|arg| arg == flag) {
+            Some(idx) => match args.get(idx + 1) {
+                Some(value) => Ok(Some(value)),
+                None => Err(format!("expected a value after `{}`", flag)),
+            },
+            None => Ok(None),
+        }
+    };
+
+    let port = match value_of("--port")? {
+        Some(port) => port.parse()?,
+        None => 8000,
+    };
+
+    let mut local_docs = Vec::new();
+    for (idx, arg) in args.iter().enumerate() {
+        if arg == "--local" {
+            let crate_paths = args[idx + 1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+            let name = crate_paths
+                .first()
+                .and_then(|path| path.file_name())
+                .ok_or("expected crate paths after `--local`")?
+                .to_string_lossy()
+                .to_string();
+            local_docs.push(LocalDoc { name, crate_paths });
+        }
+    }
+
+    let mut cli_shader_def_values = IndexMap::new();
+    for (idx, arg) in args.iter().enumerate() {
+        if arg == "--def" {
+            let def = args
+                .get(idx + 1)
+                .ok_or("expected a shader def after `--def`")?;
+            let (key, value) = compiler::parse_shader_def(def)?;
+            cli_shader_def_values.insert(key, value);
+        }
+    }
+    let defs_path = value_of("--defs")?.map(PathBuf::from);
+    let shader_def_values = || -> Result<_, Box<dyn std::error::Error>> {
+        let mut shader_def_values = cli_shader_def_values.clone();
+        if let Some(defs_path) = &defs_path {
+            shader_def_values.extend(read_shader_defs(defs_path)?);
+        }
+        Ok(shader_def_values)
+    };
+
+    let site_path;
+    let mut backend = None;
+    if local_docs.is_empty() {
+        site_path = Path::new("target/shader_docs");
+        let sink = FsSink::new(site_path);
+        let docs = compile_targets(targets, cache_path, &sink, false)?;
+        generator::generate_all(&docs, &sink, Retention::All)?;
+    } else {
+        site_path = Path::new("target/shader_docs_local");
+        let minor = value_of("--naga-oil")?.ok_or("`--local` needs `--naga-oil <minor>`")?;
+        backend = Some(
+            CompilerBackend::for_naga_oil(minor.parse()?)
+                .ok_or_else(|| format!("naga_oil 0.{} is not supported", minor))?,
+        );
+
+        let shader_def_values = shader_def_values()?;
+        for local_doc in &local_docs {
+            build_local_doc(local_doc, &shader_def_values, backend.unwrap(), site_path)?;
+        }
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!(
+        "Serving {} at http://127.0.0.1:{}",
+        site_path.display(),
+        port
+    );
+
+    let generation = Arc::new(Generation::default());
+    {
+        let site_path = site_path.to_path_buf();
+        let generation = generation.clone();
+        thread::spawn(move || serve(listener, &site_path, &generation));
+    }
+
+    let Some(backend) = backend else {
+        // The bevy sources come from crates.io and don't change, there is nothing to watch
+        loop {
+            thread::park();
+        }
+    };
+
+    let mut fingerprints = local_docs
+        .iter()
+        .map(|local_doc| fingerprint(&local_doc.crate_paths))
+        .collect::<Vec<_>>();
+    let mut defs_fingerprint = defs_path.as_deref().map(modified);
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let defs_changed = match &defs_path {
+            Some(defs_path) => {
+                let modified = Some(modified(defs_path));
+                let changed = modified != defs_fingerprint;
+                defs_fingerprint = modified;
+                changed
+            }
+            None => false,
+        };
+
+        let mut rebuilt = false;
+        for (local_doc, old_fingerprint) in local_docs.iter().zip(&mut fingerprints) {
+            let new_fingerprint = fingerprint(&local_doc.crate_paths);
+            if !defs_changed && new_fingerprint == *old_fingerprint {
+                continue;
+            }
+            *old_fingerprint = new_fingerprint;
+
+            // Broken shaders are reported and the last good pages stay up
+            println!("Rebuilding {}", local_doc.name);
+            match shader_def_values().and_then(|shader_def_values| {
+                build_local_doc(local_doc, &shader_def_values, backend, site_path)
+            }) {
+                Ok(()) => rebuilt = true,
+                Err(err) => eprintln!("Error: {}: {}", local_doc.name, err),
+            }
+        }
+
+        if rebuilt {
+            *generation.value.lock().unwrap() += 1;
+            generation.changed.notify_all();
+        }
+    }
+}
+
+fn build_local_doc(
+    local_doc: &LocalDoc,
+    shader_def_values: &IndexMap<String, ShaderDefValue>,
+    backend: CompilerBackend,
+    site_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let crate_paths = local_doc
+        .crate_paths
+        .iter()
+        .map(PathBuf::as_path)
+        .collect::<Vec<_>>();
+    // Local crates aren't published, their sources are served next to the docs
+    let doc = compiler::load_local(
+        &local_doc.name,
+        Version::new(0, 0, 0),
+        &crate_paths,
+        shader_def_values.clone(),
+        backend,
+        &SourceLinkProvider::Local,
+    )?
+    .compile()?;
+    generator::generate(&doc, &FsSink::new(site_path))
+}
+
+// One shader def per line as given to `--def`, lines starting with `#` are comments
+fn read_shader_defs(
+    path: &Path,
+) -> Result<IndexMap<String, ShaderDefValue>, Box<dyn std::error::Error>> {
+    let mut shader_def_values = IndexMap::new();
+    for line in fs::read_to_string(path)?.lines() {
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            let (key, value) = compiler::parse_shader_def(line)?;
+            shader_def_values.insert(key, value);
+        }
+    }
+    Ok(shader_def_values)
+}
+
+// Shaders below the crate paths with their modification times, any difference means the doc has
+// to be rebuilt
+fn fingerprint(crate_paths: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut files = Vec::new();
+    let mut dirs = crate_paths.to_vec();
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some("wgsl".as_ref()) {
+                let modified = modified(&path);
+                files.push((path, modified));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn serve(listener: TcpListener, site_path: &Path, generation: &Arc<Generation>) {
+    for stream in listener.incoming().flatten() {
+        let site_path = site_path.to_path_buf();
+        let generation = generation.clone();
+        thread::spawn(move || {
+            // Errors only mean that the browser went away
+            handle_connection(stream, &site_path, &generation).ok();
+        });
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    site_path: &Path,
+    generation: &Generation,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (Some("GET"), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    };
+    let url_path = target.split(['?', '#']).next().unwrap_or("/");
+
+    if url_path == LIVE_RELOAD_PATH {
+        return live_reload(stream, generation);
+    }
+
+    let relative = Path::new(url_path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return respond(&mut stream, "404 Not Found", "text/plain", b"Not found");
+    }
+
+    let mut path = site_path.join(relative);
+    if path.is_dir() {
+        // Pages link relative to their directory, which needs the trailing slash
+        if !url_path.ends_with('/') {
+            let header = format!(
+                "HTTP/1.1 301 Moved Permanently\r\nLocation: {}/\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                url_path
+            );
+            return stream.write_all(header.as_bytes());
+        }
+        path = path.join("index.html");
+    }
+
+    let Ok(body) = fs::read(&path) else {
+        return respond(&mut stream, "404 Not Found", "text/plain", b"Not found");
+    };
+
+    let content_type = content_type(&path);
+    if content_type.starts_with("text/html") {
+        let page = inject_live_reload(
+            &String::from_utf8_lossy(&body),
+            *generation.value.lock().unwrap(),
+        );
+        respond(&mut stream, "200 OK", content_type, page.as_bytes())
+    } else {
+        respond(&mut stream, "200 OK", content_type, &body)
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css",
+        Some("js") => "text/javascript",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("woff2") => "font/woff2",
+        Some("wgsl" | "rs" | "txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+// The page compares the generation it was served with against the one the server announces, so a
+// rebuild between loading the page and connecting isn't missed
+fn inject_live_reload(page: &str, generation: u64) -> String {
+    let script = format!(
+        r#"<script>new EventSource("{}").onmessage = (event) => {{ if (event.data !== "{}") location.reload(); }};</script>"#,
+        LIVE_RELOAD_PATH, generation
+    );
+    match page.rfind("</body>") {
+        Some(idx) => format!("{}{}{}", &page[..idx], script, &page[idx..]),
+        None => format!("{}{}", page, script),
+    }
+}
+
+// Server-sent events with the current generation, sent on connecting and after every rebuild
+fn live_reload(mut stream: TcpStream, generation: &Generation) -> io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n",
+    )?;
+
+    let mut sent = None;
+    loop {
+        let value = {
+            let value = generation.value.lock().unwrap();
+            let (value, _) = generation
+                .changed
+                .wait_timeout_while(value, KEEP_ALIVE_INTERVAL, |value| Some(*value) == sent)
+                .unwrap();
+            *value
+        };
+
+        if Some(value) == sent {
+            stream.write_all(b": keep-alive\n\n")?;
+        } else {
+            write!(stream, "data: {}\n\n", value)?;
+            sent = Some(value);
+        }
+        stream.flush()?;
+    }
+}
@@ -0,0 +1,63 @@
+use data_encoding::HEXLOWER;
+use sha2::{Digest, Sha256};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Called from a build script, sets `SOURCE_HASH` to a hash of the files in `source_dirs` (relative
+// to the crate), the crate's manifest and the workspace's lockfile. The lockfile has the resolved
+// versions of the dependencies, so bumping naga or askama changes the hash like a change to the
+// sources does.
+pub fn emit(source_dirs: &[&str]) {
+    let manifest_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    let mut files = vec![manifest_path.join("Cargo.toml")];
+    // Missing when the crate isn't built as part of a workspace
+    if let Some(lock_path) = manifest_path
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock_path| lock_path.is_file())
+    {
+        files.push(lock_path);
+    }
+    for dir in source_dirs {
+        println!("cargo:rerun-if-changed={}", dir);
+
+        let mut dir_files = Vec::new();
+        collect_files(&manifest_path.join(dir), &mut dir_files);
+        dir_files.sort();
+        files.extend(dir_files);
+    }
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        println!("cargo:rerun-if-changed={}", file.display());
+
+        // The same on every platform and wherever the workspace is checked out
+        let name = match file.strip_prefix(&manifest_path) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => file.file_name().unwrap().to_string_lossy().into_owned(),
+        };
+        let contents = fs::read(&file).unwrap();
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    println!(
+        "cargo:rustc-env=SOURCE_HASH={}",
+        HEXLOWER.encode(&hasher.finalize())
+    );
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(path).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}